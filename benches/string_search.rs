@@ -0,0 +1,60 @@
+//! Compares the substring searches of chapter 1 on long inputs, run with
+//! `cargo bench --bench string_search`.
+use ctci::problems::chp_1::search::SearchAlgorithm;
+use rand::Rng;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 10;
+
+/// Average time of a search over [RUNS] runs, with the number of matches found
+fn time(search: impl Fn() -> Vec<usize>) -> (Duration, usize) {
+    let matches = black_box(search()).len();
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        black_box(search());
+    }
+    (start.elapsed() / RUNS, matches)
+}
+
+fn compare(name: &str, text: &[u8], pattern: &[u8]) {
+    println!(
+        "{} ({} bytes, pattern of {} bytes)",
+        name,
+        text.len(),
+        pattern.len()
+    );
+    let algorithms = [
+        ("kmp", SearchAlgorithm::Kmp),
+        ("z", SearchAlgorithm::Z),
+        ("rabin-karp", SearchAlgorithm::RabinKarp),
+    ];
+    for (label, algorithm) in algorithms.iter() {
+        let (elapsed, matches) = time(|| algorithm.find_all(text, pattern));
+        println!("  {:<12}{:>12?}  {} matches", label, elapsed, matches);
+    }
+    let (elapsed, matches) = time(|| {
+        (0..=text.len() - pattern.len())
+            .filter(|&i| text[i..].starts_with(pattern))
+            .collect()
+    });
+    println!("  {:<12}{:>12?}  {} matches", "naive", elapsed, matches);
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let len = 4_000_000;
+
+    let random: Vec<u8> = (0..len).map(|_| rng.gen_range(b'a'..=b'z')).collect();
+    let start = rng.gen_range(0..len - 1000);
+    compare("random text", &random, &random[start..start + 1000]);
+
+    // Every window almost matches, the worst case of the naive search
+    let repeated = vec![b'a'; len];
+    let mut pattern = vec![b'a'; 1000];
+    pattern.push(b'b');
+    compare("repeated text", &repeated, &pattern);
+
+    let binary: Vec<u8> = (0..len).map(|_| rng.gen_range(b'0'..=b'1')).collect();
+    compare("binary text", &binary, &binary[..20]);
+}
@@ -7,3 +7,7 @@ pub mod problem_6;
 pub mod problem_7;
 pub mod problem_8;
 pub mod problem_9;
+
+pub mod distance;
+pub mod search;
+pub mod text;
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Single operation of an [edit_script], positions are indices in the original sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit<T> {
+    /// Inserts `value` before the element at `position` (or at the end)
+    Insert {
+        position: usize,
+        value: T,
+    },
+    Delete {
+        position: usize,
+    },
+    Substitute {
+        position: usize,
+        value: T,
+    },
+}
+
+/// # Levenshtein distance
+///
+/// Minimum number of insertions, deletions and substitutions turning `a` into `b`, using two rows
+/// of the usual dynamic programming table so it runs in O(n·m) time and O(m) space.
+pub fn levenshtein<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, x) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = prev[j] + (x != y) as usize;
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// # Damerau-Levenshtein distance
+///
+/// [levenshtein] distance where swapping two adjacent elements also counts as a single edit,
+/// even if the elements are edited again afterwards (so `"ca"` to `"abc"` is 2 edits). Keeps the
+/// last row each element was seen in, which is why the elements must be hashable.
+pub fn damerau_levenshtein<T: Eq + Hash>(a: &[T], b: &[T]) -> usize {
+    let (n, m) = (a.len(), b.len());
+    let max = n + m;
+    // Shifted by one so row and column 0 hold the maximum distance as a sentinel
+    let mut table = vec![vec![0; m + 2]; n + 2];
+    table[0][0] = max;
+    for i in 0..=n {
+        table[i + 1][0] = max;
+        table[i + 1][1] = i;
+    }
+    for j in 0..=m {
+        table[0][j + 1] = max;
+        table[1][j + 1] = j;
+    }
+
+    let mut last_row: HashMap<&T, usize> = HashMap::new();
+    for i in 1..=n {
+        let mut last_match_col = 0;
+        for j in 1..=m {
+            let k = last_row.get(&b[j - 1]).copied().unwrap_or(0);
+            let l = last_match_col;
+            let cost = if a[i - 1] == b[j - 1] {
+                last_match_col = j;
+                0
+            } else {
+                1
+            };
+            table[i + 1][j + 1] = (table[i][j] + cost)
+                .min(table[i + 1][j] + 1)
+                .min(table[i][j + 1] + 1)
+                .min(table[k][l] + (i - k - 1) + 1 + (j - l - 1));
+        }
+        last_row.insert(&a[i - 1], i);
+    }
+    table[n + 1][m + 1]
+}
+
+/// [levenshtein] distance if it is at most `k`, `None` otherwise.
+///
+/// Only the diagonal band of cells whose row and column differ by at most `k` can hold a distance
+/// of `k` or less, so this runs in O(k·n) time and stops as soon as the whole band is over `k`.
+pub fn bounded_levenshtein<T: PartialEq>(a: &[T], b: &[T], k: usize) -> Option<usize> {
+    let (n, m) = (a.len(), b.len());
+    if n.max(m) - n.min(m) > k {
+        return None;
+    }
+    let over = k + 1;
+    let in_band = |i: usize, j: usize| i.max(j) - i.min(j) <= k;
+
+    let mut prev: Vec<usize> = (0..=m).map(|j| j.min(over)).collect();
+    let mut cur = vec![over; m + 1];
+    for i in 1..=n {
+        let (lo, hi) = (i.saturating_sub(k), (i + k).min(m));
+        let mut best = over;
+        for j in lo..=hi {
+            let value = if j == 0 {
+                i
+            } else {
+                let deletion = if in_band(i - 1, j) { prev[j] + 1 } else { over };
+                let insertion = if j > lo { cur[j - 1] + 1 } else { over };
+                let substitution = prev[j - 1] + (a[i - 1] != b[j - 1]) as usize;
+                substitution.min(deletion).min(insertion)
+            };
+            cur[j] = value.min(over);
+            best = best.min(cur[j]);
+        }
+        if best > k {
+            return None;
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    Some(prev[m]).filter(|&distance| distance <= k)
+}
+
+/// Whether `a` can be turned into `b` with at most `k` edits, see [bounded_levenshtein]
+pub fn within_distance<T: PartialEq>(a: &[T], b: &[T], k: usize) -> bool {
+    bounded_levenshtein(a, b, k).is_some()
+}
+
+/// # Edit script
+///
+/// One of the shortest sequences of [Edit]s turning `a` into `b`, ordered by position. Its length
+/// is the [levenshtein] distance. Needs the whole O(n·m) table to walk back from the end.
+pub fn edit_script<T: PartialEq + Clone>(a: &[T], b: &[T]) -> Vec<Edit<T>> {
+    let (n, m) = (a.len(), b.len());
+    let mut table = vec![vec![0; m + 1]; n + 1];
+    for (i, row) in table.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in table[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let substitution = table[i - 1][j - 1] + (a[i - 1] != b[j - 1]) as usize;
+            table[i][j] = substitution
+                .min(table[i - 1][j] + 1)
+                .min(table[i][j - 1] + 1);
+        }
+    }
+
+    let mut edits = Vec::with_capacity(table[n][m]);
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && a[i - 1] == b[j - 1] && table[i][j] == table[i - 1][j - 1] {
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && j > 0 && table[i][j] == table[i - 1][j - 1] + 1 {
+            edits.push(Edit::Substitute {
+                position: i - 1,
+                value: b[j - 1].clone(),
+            });
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && table[i][j] == table[i - 1][j] + 1 {
+            edits.push(Edit::Delete { position: i - 1 });
+            i -= 1;
+        } else {
+            edits.push(Edit::Insert {
+                position: i,
+                value: b[j - 1].clone(),
+            });
+            j -= 1;
+        }
+    }
+    edits.reverse();
+    edits
+}
+
+/// Applies edits ordered by position, like the ones of [edit_script], to `a`
+pub fn apply_edits<T: Clone>(a: &[T], edits: &[Edit<T>]) -> Vec<T> {
+    let mut res = Vec::with_capacity(a.len() + edits.len());
+    let mut next = 0;
+    for edit in edits {
+        let position = match edit {
+            Edit::Insert { position, .. }
+            | Edit::Delete { position }
+            | Edit::Substitute { position, .. } => *position,
+        };
+        res.extend_from_slice(&a[next..position]);
+        next = position;
+        match edit {
+            Edit::Insert { value, .. } => res.push(value.clone()),
+            Edit::Delete { .. } => next += 1,
+            Edit::Substitute { value, .. } => {
+                res.push(value.clone());
+                next += 1;
+            }
+        }
+    }
+    res.extend_from_slice(&a[next..]);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn levenshtein_distances() {
+        let cases = [
+            ("kitten", "sitting", 3),
+            ("flaw", "lawn", 2),
+            ("", "abc", 3),
+            ("abc", "", 3),
+            ("", "", 0),
+            ("same", "same", 0),
+            ("ca", "abc", 3),
+            ("café", "cafe", 1),
+        ];
+        for &(a, b, expected) in cases.iter() {
+            let (a, b) = (chars(a), chars(b));
+            assert_eq!(levenshtein(&a, &b), expected);
+            assert_eq!(levenshtein(&b, &a), expected);
+            assert_eq!(bounded_levenshtein(&a, &b, expected), Some(expected));
+            assert_eq!(bounded_levenshtein(&a, &b, expected + 2), Some(expected));
+            if expected > 0 {
+                assert_eq!(bounded_levenshtein(&a, &b, expected - 1), None);
+            }
+        }
+    }
+
+    #[test]
+    fn damerau_levenshtein_distances() {
+        let cases = [
+            ("ca", "abc", 2),
+            ("ab", "ba", 1),
+            ("abcdef", "badcfe", 3),
+            ("kitten", "sitting", 3),
+            ("", "ab", 2),
+            ("a cat", "an act", 2),
+        ];
+        for &(a, b, expected) in cases.iter() {
+            let (a, b) = (chars(a), chars(b));
+            assert_eq!(damerau_levenshtein(&a, &b), expected);
+            assert_eq!(damerau_levenshtein(&b, &a), expected);
+            assert!(damerau_levenshtein(&a, &b) <= levenshtein(&a, &b));
+        }
+    }
+
+    #[test]
+    fn edit_scripts() {
+        let (a, b) = (chars("kitten"), chars("sitting"));
+        let edits = edit_script(&a, &b);
+        assert_eq!(
+            edits,
+            vec![
+                Edit::Substitute {
+                    position: 0,
+                    value: 's'
+                },
+                Edit::Substitute {
+                    position: 4,
+                    value: 'i'
+                },
+                Edit::Insert {
+                    position: 6,
+                    value: 'g'
+                },
+            ]
+        );
+        assert_eq!(apply_edits(&a, &edits), b);
+        assert_eq!(
+            edit_script(&chars("abc"), &chars("")),
+            vec![
+                Edit::Delete { position: 0 },
+                Edit::Delete { position: 1 },
+                Edit::Delete { position: 2 }
+            ]
+        );
+        assert!(edit_script(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn random_sequences() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..300 {
+            let a: Vec<u8> = (0..rng.gen_range(0..12))
+                .map(|_| rng.gen_range(0..4))
+                .collect();
+            let b: Vec<u8> = (0..rng.gen_range(0..12))
+                .map(|_| rng.gen_range(0..4))
+                .collect();
+            let distance = levenshtein(&a, &b);
+
+            let edits = edit_script(&a, &b);
+            assert_eq!(edits.len(), distance);
+            assert_eq!(apply_edits(&a, &edits), b);
+
+            let k = rng.gen_range(0..8);
+            assert_eq!(
+                bounded_levenshtein(&a, &b, k),
+                Some(distance).filter(|&d| d <= k)
+            );
+            assert_eq!(within_distance(&a, &b, k), distance <= k);
+            assert!(damerau_levenshtein(&a, &b) <= distance);
+        }
+    }
+}
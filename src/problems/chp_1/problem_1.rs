@@ -1,31 +1,52 @@
+use super::text::{TextOptions, Unit};
+use crate::problems::chp_5::bitset::BitSet;
 use std::collections::HashSet;
 
 pub fn all_chars_unique_part_a(s: &str) -> bool {
-    let mut char_set: HashSet<char> = HashSet::new();
+    all_chars_unique_part_a_with(s, &TextOptions::default())
+}
+
+/// Same as [all_chars_unique_part_a], comparing the units and normalisation of `options`
+pub fn all_chars_unique_part_a_with(s: &str, options: &TextOptions) -> bool {
+    let normalized = options.normalize(s);
+    let mut unit_set: HashSet<&[u8]> = HashSet::new();
 
-    for c in s.chars() {
-        if char_set.contains(&c) {
+    for unit in options.split(&normalized) {
+        if unit_set.contains(unit) {
             return false;
         }
-        char_set.insert(c);
+        unit_set.insert(unit);
     }
     true
 }
 
 pub fn all_chars_unique_part_b(s: &str) -> bool {
-    let mut bit_map: i32 = 0;
-    let base_char: u32 = 'a' as u32;
+    all_chars_unique_part_b_with(s, &TextOptions::default())
+}
 
-    for c in s.chars() {
-        let mut int_char: u32 = c as u32;
-        int_char -= base_char;
+/// Bit vector indexed by the byte or code point, so it works for any character instead of only
+/// `a` to `z`. Grapheme clusters are not numbers, so they are sorted and compared with their
+/// neighbour instead.
+pub fn all_chars_unique_part_b_with(s: &str, options: &TextOptions) -> bool {
+    let normalized = options.normalize(s);
+    let codes: Vec<usize> = match options.unit() {
+        Unit::Byte => normalized.bytes().map(|b| b as usize).collect(),
+        Unit::Char => normalized.chars().map(|c| c as usize).collect(),
+        Unit::Grapheme => {
+            let mut units = options.split(&normalized);
+            units.sort_unstable();
+            return units.windows(2).all(|pair| pair[0] != pair[1]);
+        }
+    };
 
-        if bit_map & (1 << int_char) != 0 {
+    let mut bit_map = BitSet::new();
+    for code in codes {
+        if bit_map.test(code) {
             return false;
         }
 
         // set bit
-        bit_map |= 1 << int_char;
+        bit_map.set(code);
     }
 
     true
@@ -46,4 +67,42 @@ mod tests {
         assert!(all_chars_unique_part_b(&String::from("abcdefg")));
         assert!(!all_chars_unique_part_b(&String::from("abcdefga")));
     }
+
+    #[test]
+    fn test_unicode() {
+        for &unique in [all_chars_unique_part_a_with, all_chars_unique_part_b_with].iter() {
+            let chars = TextOptions::new(Unit::Char);
+            assert!(!unique("héllo wörld", &chars));
+            assert!(!unique("ça été", &chars));
+            assert!(unique("ça êté", &chars));
+            assert!(unique("日本語", &chars));
+            assert!(unique("🦀🐍🐹", &chars));
+            assert!(!unique("🦀🐍🦀", &chars));
+
+            // é and ê share their first byte
+            assert!(!unique("éê", &TextOptions::new(Unit::Byte)));
+            assert!(unique("abc", &TextOptions::new(Unit::Byte)));
+
+            // e with a combining acute accent, then a plain e
+            let decomposed = "e\u{301}e";
+            assert!(!unique(decomposed, &chars));
+            assert!(unique(decomposed, &TextOptions::new(Unit::Grapheme)));
+            // Skin tones make different graphemes out of the same hand
+            assert!(unique("👋🏻👋🏿", &TextOptions::new(Unit::Grapheme)));
+            assert!(!unique("👋🏻👋🏻", &TextOptions::new(Unit::Grapheme)));
+
+            let folded = TextOptions::new(Unit::Char).with_case_folding();
+            assert!(!unique("Élan élu", &folded.ignoring_whitespace()));
+            assert!(unique("Ab c", &chars));
+            assert!(!unique("A b c", &chars));
+            assert!(unique("A b c", &chars.ignoring_whitespace()));
+            assert!(unique(
+                "a, b!",
+                &chars.ignoring_whitespace().ignoring_punctuation()
+            ));
+        }
+        // Characters before `a` used to underflow the bit position
+        assert!(all_chars_unique_part_b("Az? 9"));
+        assert!(!all_chars_unique_part_b("A?A"));
+    }
 }
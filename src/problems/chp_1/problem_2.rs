@@ -1,25 +1,33 @@
+use super::text::TextOptions;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
 pub fn is_permutation(s1: &str, s2: &str) -> bool {
-    if s1.len() != s2.len() {
+    is_permutation_with(s1, s2, &TextOptions::default())
+}
+
+/// Same as [is_permutation], comparing the units and normalisation of `options`
+pub fn is_permutation_with(s1: &str, s2: &str, options: &TextOptions) -> bool {
+    let (s1, s2) = (options.normalize(s1), options.normalize(s2));
+    let (units1, units2) = (options.split(&s1), options.split(&s2));
+    if units1.len() != units2.len() {
         return false;
     }
 
     let mut mappings = HashMap::new();
-    for c in s1.chars() {
-        *mappings.entry(c).or_insert(0) += 1;
+    for unit in units1 {
+        *mappings.entry(unit).or_insert(0) += 1;
     }
 
-    for c in s2.chars() {
-        match mappings.entry(c) {
+    for unit in units2 {
+        match mappings.entry(unit) {
             Entry::Vacant(_) => return false,
             Entry::Occupied(o) => {
                 let value = *o.get();
                 if value == 1 {
-                    mappings.remove(&c);
+                    mappings.remove(&unit);
                 } else {
-                    mappings.insert(c, value - 1);
+                    mappings.insert(unit, value - 1);
                 }
             }
         }
@@ -29,6 +37,7 @@ pub fn is_permutation(s1: &str, s2: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use super::super::text::Unit;
     use super::*;
 
     #[test]
@@ -36,4 +45,36 @@ mod tests {
         assert!(is_permutation("testing", "testign"));
         assert!(!is_permutation("test", "testing"));
     }
+
+    #[test]
+    fn test_is_permutation_unicode() {
+        // Same number of bytes, different characters
+        assert!(!is_permutation("éa", "aé\u{0}"));
+        assert!(is_permutation("héllo", "olléh"));
+        assert!(is_permutation("🦀🐍", "🐍🦀"));
+        assert!(!is_permutation("🦀🦀🐍", "🐍🐍🦀"));
+
+        // é Ĩ and è ĩ are made of the same bytes, in a different order
+        let bytes = TextOptions::new(Unit::Byte);
+        assert!(is_permutation_with("éĨ", "èĩ", &bytes));
+        assert!(!is_permutation("éĨ", "èĩ"));
+        assert!(!is_permutation_with("ab", "a", &bytes));
+
+        // Combining marks stay on their letter as graphemes
+        let graphemes = TextOptions::new(Unit::Grapheme);
+        let (s1, s2) = ("e\u{301}a", "a\u{301}e");
+        assert!(is_permutation(s1, s2));
+        assert!(!is_permutation_with(s1, s2, &graphemes));
+        assert!(is_permutation_with(s1, "ae\u{301}", &graphemes));
+        assert!(is_permutation_with("👋🏽👋", "👋👋🏽", &graphemes));
+        assert!(!is_permutation_with("👋🏽👋", "👋🏽👋🏽", &graphemes));
+
+        let loose = TextOptions::default()
+            .with_case_folding()
+            .ignoring_whitespace()
+            .ignoring_punctuation();
+        assert!(is_permutation_with("Dormitory", "dirty room!", &loose));
+        assert!(!is_permutation("Dormitory", "dirty room!"));
+        assert!(is_permutation_with("Ève", "vÈe", &loose));
+    }
 }
@@ -1,3 +1,7 @@
+use super::text::TextOptions;
+
+/// In place version working on `char`s, the buffer having enough room at the end for the encoded
+/// spaces. See [urlify_with] for other units and normalisations.
 pub fn urlify(input: &mut Vec<char>, true_len: usize) {
     let mut space_count: usize = 0;
     for (i, c) in input.iter().enumerate() {
@@ -22,8 +26,26 @@ pub fn urlify(input: &mut Vec<char>, true_len: usize) {
     }
 }
 
+/// Returns `input` with every whitespace encoded as `%20`, after applying the normalisation of
+/// `options`. With [Unit::Byte][super::text::Unit::Byte] the bytes of non ASCII characters are
+/// percent encoded as well, otherwise they are kept as they are (like in an IRI).
+pub fn urlify_with(input: &str, options: &TextOptions) -> String {
+    let normalized = options.normalize(input);
+    let mut res = String::with_capacity(normalized.len());
+    for unit in options.split(&normalized) {
+        match std::str::from_utf8(unit) {
+            Ok(unit) if unit.chars().all(char::is_whitespace) => res.push_str("%20"),
+            Ok(unit) => res.push_str(unit),
+            // Part of a multi-byte character
+            Err(_) => res.push_str(&format!("%{:02X}", unit[0])),
+        }
+    }
+    res
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::text::Unit;
     use super::*;
 
     #[test]
@@ -52,4 +74,30 @@ mod tests {
             input.into_iter().collect::<String>()
         )
     }
+
+    #[test]
+    fn urlify_unicode() {
+        let chars = TextOptions::default();
+        assert_eq!(urlify_with("Mr John Smith", &chars), "Mr%20John%20Smith");
+        assert_eq!(urlify_with("café crème", &chars), "café%20crème");
+        // Non breaking and ideographic spaces
+        assert_eq!(urlify_with("a\u{a0}b\u{3000}c", &chars), "a%20b%20c");
+        assert_eq!(urlify_with("🦀 rust", &chars), "🦀%20rust");
+
+        let bytes = TextOptions::new(Unit::Byte);
+        assert_eq!(urlify_with("café crème", &bytes), "caf%C3%A9%20cr%C3%A8me");
+        assert_eq!(urlify_with("🦀", &bytes), "%F0%9F%A6%80");
+
+        let graphemes = TextOptions::new(Unit::Grapheme);
+        assert_eq!(urlify_with("e\u{301} 👋🏽", &graphemes), "e\u{301}%20👋🏽");
+
+        let loose = TextOptions::default()
+            .with_case_folding()
+            .ignoring_punctuation();
+        assert_eq!(urlify_with("Hello, World!", &loose), "hello%20world");
+        assert_eq!(
+            urlify_with("Hello, World!", &loose.ignoring_whitespace()),
+            "helloworld"
+        );
+    }
 }
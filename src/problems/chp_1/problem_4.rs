@@ -1,24 +1,37 @@
+use super::text::{TextOptions, Unit};
+use crate::problems::chp_5::bitset::BitSet;
+use std::collections::HashSet;
+
 pub fn palindrome_permutation(input: &str) -> bool {
-    let mut bit_map: u64 = 0;
-    for c in input.chars() {
-        bit_map ^= 1 << (c as u32 - 'a' as u32)
-    }
+    palindrome_permutation_with(input, &TextOptions::default())
+}
 
-    let mut has_single = false;
-    for i in 0..64 {
-        if bit_map & (1 << i as u32) != 0 {
-            // Even lengths makes odd occurrences impossible
-            if input.len() % 2 == 0 {
-                return false;
+/// Whether at most one unit occurs an odd number of times, which is tracked by toggling a bit per
+/// byte or code point. Grapheme clusters are toggled in a set instead.
+///
+/// The usual version of the problem ignores case and spaces, see [TextOptions].
+pub fn palindrome_permutation_with(input: &str, options: &TextOptions) -> bool {
+    let normalized = options.normalize(input);
+    let codes: Vec<usize> = match options.unit() {
+        Unit::Byte => normalized.bytes().map(|b| b as usize).collect(),
+        Unit::Char => normalized.chars().map(|c| c as usize).collect(),
+        Unit::Grapheme => {
+            let mut odd = HashSet::new();
+            for unit in options.split(&normalized) {
+                if !odd.remove(unit) {
+                    odd.insert(unit);
+                }
             }
-            // Odd lengths can't have more than one single
-            if has_single {
-                return false;
-            }
-            has_single = true;
+            return odd.len() <= 1;
         }
+    };
+
+    let mut bit_map = BitSet::new();
+    for code in codes {
+        bit_map.toggle(code);
     }
-    true
+    // Even lengths make a single odd occurrence impossible, so this covers both lengths
+    bit_map.count_ones() <= 1
 }
 
 #[cfg(test)]
@@ -39,4 +52,37 @@ mod tests {
     fn test_palindrome_permutation_3() {
         assert!(palindrome_permutation("racecar"));
     }
+
+    #[test]
+    fn test_palindrome_permutation_unicode() {
+        let loose = TextOptions::default()
+            .with_case_folding()
+            .ignoring_whitespace();
+        assert!(palindrome_permutation_with("Tact Coa", &loose));
+        assert!(!palindrome_permutation("Tact Coa"));
+        // Accents are not removed
+        assert!(!palindrome_permutation_with(
+            "Ésope reste ici et se repose",
+            &loose
+        ));
+        assert!(palindrome_permutation_with(
+            "Été, été!",
+            &loose.ignoring_punctuation()
+        ));
+        assert!(palindrome_permutation("ééa"));
+        assert!(!palindrome_permutation("éèa"));
+        assert!(palindrome_permutation("🦀a🦀"));
+
+        // é and è only share their first byte
+        let bytes = TextOptions::new(Unit::Byte);
+        assert!(!palindrome_permutation_with("éè", &bytes));
+        assert!(palindrome_permutation_with("éé", &bytes));
+
+        // Two clusters for the same e, one with an accent
+        let graphemes = TextOptions::new(Unit::Grapheme);
+        assert!(palindrome_permutation("e\u{301}e"));
+        assert!(!palindrome_permutation_with("e\u{301}ex", &graphemes));
+        assert!(palindrome_permutation_with("e\u{301}xe\u{301}", &graphemes));
+        assert!(palindrome_permutation_with("", &graphemes));
+    }
 }
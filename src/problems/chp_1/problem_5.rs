@@ -1,58 +1,25 @@
-use std::cmp::Ordering;
+use super::distance::within_distance;
+use super::text::TextOptions;
 
-fn has_one_edit(s1: &str, s2: &str) -> bool {
-    assert!((s1.len() as i32 - s2.len() as i32).abs() <= 1);
-
-    let mut has_edit = false;
-    let mut s1_iter = s1.chars();
-    let mut s2_iter = s2.chars();
-    let mut c1_opt = s1_iter.next();
-    let mut c2_opt = s2_iter.next();
-
-    loop {
-        match (c1_opt, c2_opt) {
-            (Some(c1), Some(c2)) => {
-                if c1 == c2 {
-                    c1_opt = s1_iter.next();
-                    c2_opt = s2_iter.next();
-                } else {
-                    if has_edit {
-                        return false;
-                    }
-                    has_edit = true;
-                    match s1.len().cmp(&s2.len()) {
-                        Ordering::Greater => {
-                            c1_opt = s1_iter.next();
-                        }
-                        Ordering::Less => {
-                            c2_opt = s2_iter.next();
-                        }
-                        Ordering::Equal => {
-                            c1_opt = s1_iter.next();
-                            c2_opt = s2_iter.next();
-                        }
-                    }
-                }
-            }
-            (Some(_), None) => return !has_edit && s1.len() < s2.len(),
-            (None, Some(_)) => return !has_edit && s1.len() < s2.len(),
-            _ => return true,
-        }
-    }
+pub fn one_away(s1: &str, s2: &str) -> bool {
+    one_away_with(s1, s2, &TextOptions::default())
 }
 
-pub fn one_away(s1: &str, s2: &str) -> bool {
+/// Same as [one_away], comparing the units and normalisation of `options`. Lengths are counted
+/// in units, so replacing an accented letter is a single edit.
+///
+/// [within_distance] answers the same question for any number of edits.
+pub fn one_away_with(s1: &str, s2: &str, options: &TextOptions) -> bool {
+    let (s1, s2) = (options.normalize(s1), options.normalize(s2));
     if s1 == s2 {
         return true;
     }
-    if (s1.len() as i32 - s2.len() as i32).abs() > 1 {
-        return false;
-    }
-    has_one_edit(s1, s2)
+    within_distance(&options.split(&s1), &options.split(&s2), 1)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::text::Unit;
     use super::*;
 
     #[test]
@@ -69,4 +36,42 @@ mod tests {
     fn test_one_away_3() {
         assert!(!one_away("pace", "bake"));
     }
+
+    #[test]
+    fn test_one_away_edges() {
+        assert!(one_away("", "a"));
+        assert!(one_away("abc", "ab"));
+        assert!(one_away("abc", "bc"));
+        assert!(!one_away("abc", "a"));
+        assert!(!one_away("ab", "ba"));
+    }
+
+    #[test]
+    fn test_one_away_unicode() {
+        // One more byte but the same number of characters
+        assert!(one_away("cafe", "café"));
+        assert!(one_away("naïve", "naive"));
+        assert!(one_away("🦀🐍", "🦀🐹"));
+        assert!(one_away("🦀", "🦀🐍"));
+        assert!(!one_away("🦀", "🐍🐹"));
+        // A two byte character replaced by another one is two byte edits
+        assert!(!one_away_with("é", "ā", &TextOptions::new(Unit::Byte)));
+        assert!(one_away_with("é", "è", &TextOptions::new(Unit::Byte)));
+        assert!(one_away_with("é", "é", &TextOptions::new(Unit::Byte)));
+
+        // Removing both accents of a decomposed letter is a single grapheme edit
+        let graphemes = TextOptions::new(Unit::Grapheme);
+        assert!(!one_away("e\u{301}\u{302}x", "ex"));
+        assert!(one_away_with("e\u{301}\u{302}x", "ex", &graphemes));
+        assert!(one_away_with("👋🏽!", "👋🏿!", &graphemes));
+        assert!(!one_away_with("👋🏽👋🏽", "👋🏿👋🏿", &graphemes));
+
+        let loose = TextOptions::default()
+            .with_case_folding()
+            .ignoring_whitespace()
+            .ignoring_punctuation();
+        assert!(one_away_with("Hello, World!", "hello world", &loose));
+        assert!(one_away_with("PÂTE", "pâté", &loose));
+        assert!(!one_away_with("PATE", "pâté", &loose));
+    }
 }
@@ -1,3 +1,8 @@
+mod rle;
+pub use rle::*;
+
+/// Output which cannot always be decoded, since digits of the input look like counts and a
+/// string which does not get shorter is returned as it is. [rle_encode] is reversible.
 pub fn string_compression(s1: &str) -> String {
     let mut builder = String::new();
     let mut last_char: Option<(char, usize)> = None;
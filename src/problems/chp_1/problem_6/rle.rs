@@ -0,0 +1,590 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RleErr {
+    /// The input stops in the middle of a run
+    UnexpectedEnd,
+    /// A run count of zero
+    ZeroCount,
+    /// A run count does not fit in a `usize`
+    CountOverflow,
+    /// The decoded output would be longer than [MAX_DECODED_LEN]
+    TooLong,
+}
+
+impl fmt::Display for RleErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of the encoded input"),
+            Self::ZeroCount => write!(f, "run count of zero"),
+            Self::CountOverflow => write!(f, "run count overflow"),
+            Self::TooLong => write!(f, "decoded output longer than {}", MAX_DECODED_LEN),
+        }
+    }
+}
+
+/// Unit of the textual run length encoding, either a byte or a char
+trait Symbol: Copy + PartialEq {
+    const ESCAPE: Self;
+
+    fn digit(self) -> Option<usize>;
+
+    fn from_digit(digit: u8) -> Self;
+}
+
+impl Symbol for u8 {
+    const ESCAPE: Self = b'\\';
+
+    fn digit(self) -> Option<usize> {
+        Some(self)
+            .filter(u8::is_ascii_digit)
+            .map(|b| (b - b'0') as usize)
+    }
+
+    fn from_digit(digit: u8) -> Self {
+        b'0' + digit
+    }
+}
+
+impl Symbol for char {
+    const ESCAPE: Self = '\\';
+
+    fn digit(self) -> Option<usize> {
+        Some(self)
+            .filter(char::is_ascii_digit)
+            .map(|c| (c as u8 - b'0') as usize)
+    }
+
+    fn from_digit(digit: u8) -> Self {
+        (b'0' + digit) as char
+    }
+}
+
+fn encode_runs<T: Symbol>(input: impl IntoIterator<Item = T>) -> Vec<T> {
+    let mut res = Vec::new();
+    let mut push_run = |symbol: T, count: usize| {
+        if count >= 2 {
+            res.extend(count.to_string().bytes().map(|b| T::from_digit(b - b'0')));
+        }
+        if symbol.digit().is_some() || symbol == T::ESCAPE {
+            res.push(T::ESCAPE);
+        }
+        res.push(symbol);
+    };
+
+    let mut run: Option<(T, usize)> = None;
+    for symbol in input {
+        run = match run {
+            Some((last, count)) if last == symbol => Some((last, count + 1)),
+            Some((last, count)) => {
+                push_run(last, count);
+                Some((symbol, 1))
+            }
+            None => Some((symbol, 1)),
+        };
+    }
+    if let Some((last, count)) = run {
+        push_run(last, count);
+    }
+    res
+}
+
+/// Longest output of [rle_decode] and [rle_decode_bytes], in chars or bytes. A few digits can
+/// stand for a huge run, so a malformed input could otherwise exhaust the memory.
+pub const MAX_DECODED_LEN: usize = 1 << 30;
+
+fn decode_runs<T: Symbol>(input: impl IntoIterator<Item = T>) -> Result<Vec<T>, RleErr> {
+    let mut res = Vec::new();
+    let mut input = input.into_iter().peekable();
+    loop {
+        let mut count: Option<usize> = None;
+        while let Some(digit) = input.peek().and_then(|symbol| symbol.digit()) {
+            count = Some(
+                count
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|count| count.checked_add(digit))
+                    .ok_or(RleErr::CountOverflow)?,
+            );
+            input.next();
+        }
+        let symbol = match input.next() {
+            Some(symbol) if symbol == T::ESCAPE => input.next().ok_or(RleErr::UnexpectedEnd)?,
+            Some(symbol) => symbol,
+            None if count.is_some() => return Err(RleErr::UnexpectedEnd),
+            None => return Ok(res),
+        };
+        let count = match count {
+            Some(0) => return Err(RleErr::ZeroCount),
+            count => count.unwrap_or(1),
+        };
+        if count > MAX_DECODED_LEN - res.len() {
+            return Err(RleErr::TooLong);
+        }
+        res.extend(std::iter::repeat_n(symbol, count));
+    }
+}
+
+/// # Reversible run length encoding
+///
+/// Same output as [string_compression][super::string_compression] (runs of two or more chars
+/// become their count followed by the char), except that digits and `\` are escaped with a `\`
+/// so they cannot be mistaken for counts, and the encoding is returned even when it is longer.
+/// `"a11\\"` is encoded as `"a2\\1\\\\"`.
+pub fn rle_encode(s: &str) -> String {
+    encode_runs(s.chars()).into_iter().collect()
+}
+
+/// Decodes the output of [rle_encode], failing if it is longer than [MAX_DECODED_LEN] chars
+pub fn rle_decode(s: &str) -> Result<String, RleErr> {
+    decode_runs(s.chars()).map(|chars| chars.into_iter().collect())
+}
+
+/// Same as [rle_encode] over bytes, so the runs of multi-byte characters are not compressed
+pub fn rle_encode_bytes(data: &[u8]) -> Vec<u8> {
+    encode_runs(data.iter().copied())
+}
+
+/// Decodes the output of [rle_encode_bytes], failing if it is longer than [MAX_DECODED_LEN] bytes
+pub fn rle_decode_bytes(data: &[u8]) -> Result<Vec<u8>, RleErr> {
+    decode_runs(data.iter().copied())
+}
+
+/// Longest literal or repeated packet of [pack_bits]
+const MAX_PACKET: usize = 128;
+
+/// Incremental [pack_bits] encoder, writing complete packets to `out` as bytes are pushed
+#[derive(Debug, Default)]
+struct PackBitsEncoder {
+    literals: Vec<u8>,
+    run: Option<(u8, usize)>,
+}
+
+impl PackBitsEncoder {
+    fn push(&mut self, byte: u8, out: &mut Vec<u8>) {
+        match self.run {
+            Some((last, len)) if last == byte => {
+                self.run = Some((last, len + 1));
+                if len + 1 == MAX_PACKET {
+                    self.end_run(out);
+                }
+            }
+            _ => {
+                self.end_run(out);
+                self.run = Some((byte, 1));
+            }
+        }
+    }
+
+    /// Runs shorter than 3 bytes are cheaper as literals
+    fn end_run(&mut self, out: &mut Vec<u8>) {
+        match self.run.take() {
+            Some((byte, len)) if len >= 3 => {
+                self.flush_literals(out);
+                out.push((257 - len) as u8);
+                out.push(byte);
+            }
+            Some((byte, len)) => {
+                for _ in 0..len {
+                    self.literals.push(byte);
+                    if self.literals.len() == MAX_PACKET {
+                        self.flush_literals(out);
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn flush_literals(&mut self, out: &mut Vec<u8>) {
+        if !self.literals.is_empty() {
+            out.push((self.literals.len() - 1) as u8);
+            out.append(&mut self.literals);
+        }
+    }
+
+    /// Writes the pending bytes, the encoder can still be used afterwards
+    fn finish(&mut self, out: &mut Vec<u8>) {
+        self.end_run(out);
+        self.flush_literals(out);
+    }
+}
+
+/// # Binary run length encoding
+///
+/// PackBits format, a sequence of packets starting with a header byte `n`:
+/// - `0..=127`: the next `n + 1` bytes are copied as they are
+/// - `129..=255`: the next byte is repeated `257 - n` times
+/// - `128`: empty packet, skipped
+///
+/// So the output is at most one byte per 128 longer than the input. [RleWriter] and [RleReader]
+/// encode and decode the same format on the fly.
+pub fn pack_bits(data: &[u8]) -> Vec<u8> {
+    let mut encoder = PackBitsEncoder::default();
+    let mut res = Vec::with_capacity(data.len() + data.len() / MAX_PACKET + 1);
+    for &byte in data {
+        encoder.push(byte, &mut res);
+    }
+    encoder.finish(&mut res);
+    res
+}
+
+/// Decodes the output of [pack_bits]
+pub fn unpack_bits(data: &[u8]) -> Result<Vec<u8>, RleErr> {
+    let mut res = Vec::new();
+    let mut pos = 0;
+    while let Some(&header) = data.get(pos) {
+        pos += 1;
+        match header {
+            0..=127 => {
+                let end = pos + header as usize + 1;
+                res.extend_from_slice(data.get(pos..end).ok_or(RleErr::UnexpectedEnd)?);
+                pos = end;
+            }
+            128 => {}
+            _ => {
+                let &byte = data.get(pos).ok_or(RleErr::UnexpectedEnd)?;
+                res.extend(std::iter::repeat_n(byte, 257 - header as usize));
+                pos += 1;
+            }
+        }
+    }
+    Ok(res)
+}
+
+/// # Streaming encoder
+///
+/// Writes the [pack_bits] encoding of everything written to it into `inner`. Runs can span
+/// several writes, but flushing ends the current packet. Pending bytes are written when the
+/// writer is [finished][Self::finish] or dropped, ignoring errors in the latter case.
+pub struct RleWriter<W: Write> {
+    inner: Option<W>,
+    encoder: PackBitsEncoder,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> RleWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner: Some(inner),
+            encoder: PackBitsEncoder::default(),
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
+
+    /// Writes the pending bytes and returns the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner.take().unwrap())
+    }
+
+    fn write_encoded(&mut self) -> io::Result<()> {
+        let res = self.inner.as_mut().unwrap().write_all(&self.buf);
+        self.buf.clear();
+        res
+    }
+}
+
+impl<W: Write> Write for RleWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            self.encoder.push(byte, &mut self.buf);
+        }
+        self.write_encoded()?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder.finish(&mut self.buf);
+        self.write_encoded()?;
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for RleWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.flush();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Packet {
+    Header,
+    Literal(usize),
+    Run(u8, usize),
+}
+
+/// # Streaming decoder
+///
+/// Reads the [pack_bits] encoded `inner` and returns the decoded bytes. The headers are read one
+/// byte at a time, so `inner` should be buffered. An input ending in the middle of a packet is an
+/// [UnexpectedEof][io::ErrorKind::UnexpectedEof] error.
+pub struct RleReader<R: Read> {
+    inner: R,
+    packet: Packet,
+}
+
+impl<R: Read> RleReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            packet: Packet::Header,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        loop {
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for RleReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() {
+            match self.packet {
+                Packet::Header => {
+                    // Do not block on the next packet once there is something to return
+                    if written > 0 {
+                        break;
+                    }
+                    let header = match self.read_byte()? {
+                        Some(header) => header,
+                        None => break,
+                    };
+                    self.packet = match header {
+                        0..=127 => Packet::Literal(header as usize + 1),
+                        128 => Packet::Header,
+                        _ => match self.read_byte()? {
+                            Some(byte) => Packet::Run(byte, 257 - header as usize),
+                            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+                        },
+                    };
+                }
+                Packet::Literal(left) => {
+                    let len = left.min(buf.len() - written);
+                    let read = self.inner.read(&mut buf[written..written + len])?;
+                    if read == 0 {
+                        return Err(io::ErrorKind::UnexpectedEof.into());
+                    }
+                    written += read;
+                    self.packet = match left - read {
+                        0 => Packet::Header,
+                        left => Packet::Literal(left),
+                    };
+                }
+                Packet::Run(byte, left) => {
+                    let len = left.min(buf.len() - written);
+                    buf[written..written + len].fill(byte);
+                    written += len;
+                    self.packet = match left - len {
+                        0 => Packet::Header,
+                        left => Packet::Run(byte, left),
+                    };
+                }
+            }
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::string_compression;
+    use super::*;
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+
+    /// Runs of random lengths, including long ones, of symbols which need escaping
+    fn random_text(rng: &mut impl Rng) -> String {
+        let alphabet = ['a', 'b', '1', '0', '\\', 'é', '\u{1F600}', ' ', '\n'];
+        let mut s = String::new();
+        for _ in 0..rng.gen_range(0..20) {
+            let c = *alphabet.choose(rng).unwrap();
+            let len = if rng.gen_bool(0.1) {
+                rng.gen_range(100..400)
+            } else {
+                rng.gen_range(1..5)
+            };
+            s.extend(std::iter::repeat(c).take(len));
+        }
+        s
+    }
+
+    fn random_bytes(rng: &mut impl Rng) -> Vec<u8> {
+        let mut data = Vec::new();
+        for _ in 0..rng.gen_range(0..30) {
+            if rng.gen_bool(0.5) {
+                let len = rng.gen_range(1..300);
+                data.extend((0..len).map(|_| rng.gen::<u8>()));
+            } else {
+                let len = rng.gen_range(1..400);
+                data.extend(std::iter::repeat(rng.gen::<u8>()).take(len));
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn text_encoding() {
+        assert_eq!(rle_encode("heeeeaaavven"), "h4e3a2ven");
+        assert_eq!(rle_encode("a11\\"), "a2\\1\\\\");
+        assert_eq!(rle_encode(&"7".repeat(12)), "12\\7");
+        assert_eq!(rle_encode("ééé"), "3é");
+        assert_eq!(rle_encode(""), "");
+        assert_eq!(rle_decode("2\\1\\\\x10y").unwrap(), "11\\xyyyyyyyyyy");
+
+        assert_eq!(rle_encode_bytes(b"aaa12"), b"3a\\1\\2".to_vec());
+        // Both bytes of the `é` differ
+        assert_eq!(rle_encode_bytes("éé".as_bytes()), "éé".as_bytes().to_vec());
+        assert_eq!(rle_decode_bytes(b"3\\0").unwrap(), b"000".to_vec());
+    }
+
+    #[test]
+    fn text_decoding_errors() {
+        assert_eq!(rle_decode("a12"), Err(RleErr::UnexpectedEnd));
+        assert_eq!(rle_decode("ab\\"), Err(RleErr::UnexpectedEnd));
+        assert_eq!(rle_decode("0a"), Err(RleErr::ZeroCount));
+        assert_eq!(
+            rle_decode("99999999999999999999999a"),
+            Err(RleErr::CountOverflow)
+        );
+        assert_eq!(rle_decode_bytes(b"5"), Err(RleErr::UnexpectedEnd));
+
+        assert_eq!(rle_decode("18446744073709551615a"), Err(RleErr::TooLong));
+        // The first run leaves no room for the second one
+        let input = format!("a{}b", MAX_DECODED_LEN);
+        assert_eq!(rle_decode_bytes(input.as_bytes()), Err(RleErr::TooLong));
+    }
+
+    #[test]
+    fn pack_bits_format() {
+        let data = [
+            0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0xAA, 0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0x22,
+            0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA,
+        ];
+        let packed = [
+            0xFE, 0xAA, 0x02, 0x80, 0x00, 0x2A, 0xFD, 0xAA, 0x03, 0x80, 0x00, 0x2A, 0x22, 0xF7,
+            0xAA,
+        ];
+        assert_eq!(pack_bits(&data), packed.to_vec());
+        assert_eq!(unpack_bits(&packed).unwrap(), data.to_vec());
+
+        // Long runs and literals are split in packets of at most 128 bytes
+        assert_eq!(pack_bits(&[7; 300]), vec![0x81, 7, 0x81, 7, 0xD5, 7]);
+        let literals: Vec<u8> = (0..=255).collect();
+        let packed = pack_bits(&literals);
+        assert_eq!(packed.len(), 258);
+        assert_eq!((packed[0], packed[129]), (127, 127));
+
+        assert_eq!(unpack_bits(&[0x80, 0x01, 1, 2, 0x80]).unwrap(), vec![1, 2]);
+        assert_eq!(unpack_bits(&[0x02, 1, 2]), Err(RleErr::UnexpectedEnd));
+        assert_eq!(unpack_bits(&[0xFE]), Err(RleErr::UnexpectedEnd));
+        assert!(pack_bits(&[]).is_empty());
+    }
+
+    #[test]
+    fn streaming() {
+        let data = b"aaaaaaaaaabcdefgggg".repeat(20);
+        let mut writer = RleWriter::new(Vec::new());
+        for chunk in data.chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        let packed = writer.finish().unwrap();
+        // Runs spanning several writes are still compressed
+        assert_eq!(packed, pack_bits(&data));
+
+        let mut decoded = Vec::new();
+        RleReader::new(packed.as_slice())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data);
+
+        let mut dropped = Vec::new();
+        {
+            let mut writer = RleWriter::new(&mut dropped);
+            writer.write_all(b"xxxx").unwrap();
+        }
+        assert_eq!(dropped, vec![0xFD, b'x']);
+
+        let err = RleReader::new(&[0x05, 1, 2][..])
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let s = random_text(&mut rng);
+            assert_eq!(rle_decode(&rle_encode(&s)).unwrap(), s);
+            assert_eq!(
+                rle_decode_bytes(&rle_encode_bytes(s.as_bytes())).unwrap(),
+                s.as_bytes()
+            );
+
+            // Without anything to escape, the encoding matches string_compression when the
+            // latter compresses
+            let plain: String = s.chars().filter(|&c| c.is_alphabetic()).collect();
+            let compressed = string_compression(&plain);
+            if compressed.len() < plain.len() {
+                assert_eq!(rle_encode(&plain), compressed);
+            }
+        }
+    }
+
+    #[test]
+    fn binary_round_trips() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let data = random_bytes(&mut rng);
+            let packed = pack_bits(&data);
+            assert!(packed.len() <= data.len() + data.len().div_ceil(MAX_PACKET));
+            assert_eq!(unpack_bits(&packed).unwrap(), data);
+
+            let mut writer = RleWriter::new(Vec::new());
+            let mut rest = data.as_slice();
+            while !rest.is_empty() {
+                let (chunk, tail) = rest.split_at(rng.gen_range(1..=rest.len()));
+                writer.write_all(chunk).unwrap();
+                if rng.gen_bool(0.1) {
+                    writer.flush().unwrap();
+                }
+                rest = tail;
+            }
+            let streamed = writer.finish().unwrap();
+            assert_eq!(unpack_bits(&streamed).unwrap(), data);
+
+            let mut reader = RleReader::new(streamed.as_slice());
+            let mut decoded = Vec::new();
+            let mut buf = [0; 37];
+            loop {
+                let len = rng.gen_range(1..=buf.len());
+                match reader.read(&mut buf[..len]).unwrap() {
+                    0 => break,
+                    read => decoded.extend_from_slice(&buf[..read]),
+                }
+            }
+            assert_eq!(decoded, data);
+        }
+    }
+}
@@ -1,22 +1,9 @@
-pub fn rotate_image_90(image: &mut [Vec<u32>]) {
-    let len = image.len();
-    // Transponse the matrix on diagonal
-    for x in 0..len {
-        for y in 0..len - x - 1 {
-            let tmp = image[x][y];
-            image[x][y] = image[len - y - 1][len - x - 1];
-            image[len - y - 1][len - x - 1] = tmp;
-        }
-    }
+use crate::structures::Grid;
 
-    // Flip on horizontal
-    for y in 0..len {
-        for x in 0..(len / 2) {
-            let tmp = image[x][y];
-            image[x][y] = image[len - x - 1][y];
-            image[len - x - 1][y] = tmp;
-        }
-    }
+/// Rotates the image a quarter turn clockwise, see [Grid::rotate_90]. Square images are
+/// rotated in place by transposing them and mirroring every row.
+pub fn rotate_image_90<T: Clone>(image: &mut Grid<T>) {
+    image.rotate_90()
 }
 
 #[cfg(test)]
@@ -25,20 +12,21 @@ mod tests {
 
     #[test]
     fn test_rotate_image_90_1() {
-        let mut image = vec![vec![1, 2], vec![3, 4]];
+        let mut image = Grid::from_rows(vec![vec![1, 2], vec![3, 4]]).unwrap();
         rotate_image_90(&mut image);
         let expected = vec![vec![3, 1], vec![4, 2]];
-        assert_eq!(expected, image);
+        assert_eq!(expected, image.to_rows());
     }
 
     #[test]
     fn test_rotate_image_90_2() {
-        let mut image = vec![
+        let mut image = Grid::from_rows(vec![
             vec![1, 2, 3, 4],
             vec![5, 6, 7, 8],
             vec![9, 10, 11, 12],
             vec![13, 14, 15, 16],
-        ];
+        ])
+        .unwrap();
         rotate_image_90(&mut image);
         let expected = vec![
             vec![13, 9, 5, 1],
@@ -46,18 +34,19 @@ mod tests {
             vec![15, 11, 7, 3],
             vec![16, 12, 8, 4],
         ];
-        assert_eq!(expected, image);
+        assert_eq!(expected, image.to_rows());
     }
 
     #[test]
     fn test_rotate_image_90_3() {
-        let mut image = vec![
+        let mut image = Grid::from_rows(vec![
             vec![1, 2, 3, 4, 5],
             vec![6, 7, 8, 9, 10],
             vec![11, 12, 13, 14, 15],
             vec![16, 17, 18, 19, 20],
             vec![21, 22, 23, 24, 25],
-        ];
+        ])
+        .unwrap();
         rotate_image_90(&mut image);
         let expected = vec![
             vec![21, 16, 11, 6, 1],
@@ -66,6 +55,13 @@ mod tests {
             vec![24, 19, 14, 9, 4],
             vec![25, 20, 15, 10, 5],
         ];
-        assert_eq!(expected, image);
+        assert_eq!(expected, image.to_rows());
+    }
+
+    #[test]
+    fn test_rotate_image_90_rectangular() {
+        let mut image = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        rotate_image_90(&mut image);
+        assert_eq!(image.to_rows(), vec![vec![4, 1], vec![5, 2], vec![6, 3]]);
     }
 }
@@ -1,8 +1,26 @@
+use super::search::kmp_search;
+
 pub fn is_rotation(s1: &str, s2: &str) -> bool {
+    rotation_offset(s1, s2).is_some()
+}
+
+/// Smallest byte offset `k` such that `s2` is `s1[k..]` followed by `s1[..k]`, `None` if `s2` is
+/// not a rotation of `s1`. The offset is always on a char boundary of `s1`.
+///
+/// Rotations of `s1` are the substrings of `s1s1` with the same length, so this looks for the
+/// first occurrence of `s2` in `s1s1` with [kmp_search], in linear time.
+pub fn rotation_offset(s1: &str, s2: &str) -> Option<usize> {
     if s1.len() != s2.len() {
-        return false;
+        return None;
+    }
+    if s1.is_empty() {
+        return Some(0);
     }
-    s2.repeat(2).contains(s1)
+    let doubled = s1.repeat(2);
+    // Dropping the last byte excludes a start at s1.len(), which would repeat offset 0
+    kmp_search(&doubled.as_bytes()[..2 * s1.len() - 1], s2.as_bytes())
+        .first()
+        .copied()
 }
 
 #[cfg(test)]
@@ -23,4 +41,17 @@ mod tests {
     fn test_is_rotation_3() {
         assert!(is_rotation("aaa", "aaa"))
     }
+
+    #[test]
+    fn test_rotation_offset() {
+        assert_eq!(rotation_offset("waterbottle", "erbottlewat"), Some(3));
+        assert_eq!(rotation_offset("waterbottle", "waterbottle"), Some(0));
+        assert_eq!(rotation_offset("abab", "baba"), Some(1));
+        assert_eq!(rotation_offset("abc", "acb"), None);
+        assert_eq!(rotation_offset("abc", "ab"), None);
+        assert_eq!(rotation_offset("", ""), Some(0));
+        // Offsets are in bytes
+        assert_eq!(rotation_offset("été", "téé"), Some(2));
+        assert!(!is_rotation("ab", "ba\u{0}"));
+    }
 }
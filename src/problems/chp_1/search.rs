@@ -0,0 +1,235 @@
+/// Substring search algorithm used by [SearchAlgorithm::find_all]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchAlgorithm {
+    #[default]
+    Kmp,
+    Z,
+    RabinKarp,
+}
+
+impl SearchAlgorithm {
+    pub fn find_all<T: Copy + PartialEq + Into<u32>>(
+        self,
+        text: &[T],
+        pattern: &[T],
+    ) -> Vec<usize> {
+        match self {
+            Self::Kmp => kmp_search(text, pattern),
+            Self::Z => z_search(text, pattern),
+            Self::RabinKarp => rabin_karp_search(text, pattern),
+        }
+    }
+}
+
+/// Length of the longest proper prefix of `pattern[..=i]` which is also one of its suffixes, for
+/// every `i`
+fn prefix_function<T: PartialEq>(pattern: &[T]) -> Vec<usize> {
+    let mut prefix = vec![0; pattern.len()];
+    let mut len = 0;
+    for i in 1..pattern.len() {
+        while len > 0 && pattern[i] != pattern[len] {
+            len = prefix[len - 1];
+        }
+        if pattern[i] == pattern[len] {
+            len += 1;
+        }
+        prefix[i] = len;
+    }
+    prefix
+}
+
+/// # Knuth-Morris-Pratt
+///
+/// Start of every, possibly overlapping, occurrence of `pattern` in `text`. On a mismatch the
+/// [prefix function][prefix_function] of the pattern tells how much of it is still matched, so
+/// the text is never read backwards and the search runs in O(n + m).
+///
+/// An empty pattern matches at every position, including the end of the text.
+pub fn kmp_search<T: PartialEq>(text: &[T], pattern: &[T]) -> Vec<usize> {
+    if pattern.is_empty() {
+        return (0..=text.len()).collect();
+    }
+    let prefix = prefix_function(pattern);
+    let mut matches = Vec::new();
+    let mut len = 0;
+    for (i, x) in text.iter().enumerate() {
+        while len > 0 && *x != pattern[len] {
+            len = prefix[len - 1];
+        }
+        if *x == pattern[len] {
+            len += 1;
+        }
+        if len == pattern.len() {
+            matches.push(i + 1 - len);
+            len = prefix[len - 1];
+        }
+    }
+    matches
+}
+
+/// Z-array of the sequence of `len` elements where `eq(i, j)` compares the elements `i` and `j`
+fn z_values(len: usize, eq: impl Fn(usize, usize) -> bool) -> Vec<usize> {
+    let mut z = vec![0; len];
+    if len > 0 {
+        z[0] = len;
+    }
+    // Rightmost window [left, right) matching a prefix
+    let (mut left, mut right) = (0, 0);
+    for i in 1..len {
+        if i < right {
+            z[i] = z[i - left].min(right - i);
+        }
+        while i + z[i] < len && eq(z[i], i + z[i]) {
+            z[i] += 1;
+        }
+        if i + z[i] > right {
+            left = i;
+            right = i + z[i];
+        }
+    }
+    z
+}
+
+/// # Z-array
+///
+/// Length of the longest common prefix of `s` and `s[i..]` for every `i`, computed in O(n) by
+/// reusing the rightmost window known to match a prefix.
+pub fn z_array<T: PartialEq>(s: &[T]) -> Vec<usize> {
+    z_values(s.len(), |i, j| s[i] == s[j])
+}
+
+/// # Z-algorithm search
+///
+/// Same matches as [kmp_search], using the [Z-array][z_array] of the pattern followed by the
+/// text: the pattern starts wherever the common prefix is at least as long as the pattern. The
+/// concatenation is never built, elements are read from either slice.
+pub fn z_search<T: PartialEq>(text: &[T], pattern: &[T]) -> Vec<usize> {
+    let m = pattern.len();
+    let at = |i: usize| if i < m { &pattern[i] } else { &text[i - m] };
+    let z = z_values(m + text.len(), |i, j| at(i) == at(j));
+    (0..=text.len())
+        .filter(|&i| i == text.len() && m == 0 || z.get(m + i).is_some_and(|&len| len >= m))
+        .collect()
+}
+
+/// Mersenne prime modulus of the [rabin_karp_search] hashes
+const MODULUS: u64 = (1 << 61) - 1;
+/// Larger than any `char`, so different short sequences have different hashes
+const BASE: u64 = 0x11_0003;
+
+/// Product of `a` and `b`, both smaller than [MODULUS]. Since 2^61 is 1 modulo a Mersenne prime,
+/// the high bits of the product are added to the low ones instead of dividing.
+fn mul_mod(a: u64, b: u64) -> u64 {
+    let product = a as u128 * b as u128;
+    reduce((product as u64 & MODULUS) + (product >> 61) as u64)
+}
+
+/// `x` modulo [MODULUS], for `x` smaller than twice the modulus
+fn reduce(x: u64) -> u64 {
+    if x >= MODULUS {
+        x - MODULUS
+    } else {
+        x
+    }
+}
+
+fn hash<T: Copy + Into<u32>>(s: &[T]) -> u64 {
+    s.iter().fold(0, |hash, &x| {
+        reduce(mul_mod(hash, BASE) + u64::from(x.into()))
+    })
+}
+
+/// # Rabin-Karp
+///
+/// Same matches as [kmp_search], comparing a rolling polynomial hash of every window of the text
+/// with the hash of the pattern. Windows whose hash matches are compared element by element, so
+/// collisions only cost time: O(n + m) expected, O(n·m) in the worst case.
+pub fn rabin_karp_search<T: Copy + PartialEq + Into<u32>>(text: &[T], pattern: &[T]) -> Vec<usize> {
+    let m = pattern.len();
+    if m == 0 {
+        return (0..=text.len()).collect();
+    }
+    if m > text.len() {
+        return Vec::new();
+    }
+    let target = hash(pattern);
+    // Weight of the element leaving the window
+    let high = (1..m).fold(1, |power, _| mul_mod(power, BASE));
+
+    let mut matches = Vec::new();
+    let mut window = hash(&text[..m]);
+    for i in 0..=text.len() - m {
+        if window == target && text[i..i + m] == *pattern {
+            matches.push(i);
+        }
+        if i + m < text.len() {
+            let leaving = mul_mod(u64::from(text[i].into()), high);
+            window = reduce(window + MODULUS - leaving);
+            window = reduce(mul_mod(window, BASE) + u64::from(text[i + m].into()));
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    const ALGORITHMS: [SearchAlgorithm; 3] = [
+        SearchAlgorithm::Kmp,
+        SearchAlgorithm::Z,
+        SearchAlgorithm::RabinKarp,
+    ];
+
+    fn naive_search<T: PartialEq>(text: &[T], pattern: &[T]) -> Vec<usize> {
+        (0..=text.len())
+            .filter(|&i| text[i..].starts_with(pattern))
+            .collect()
+    }
+
+    #[test]
+    fn all_matches() {
+        for algorithm in ALGORITHMS.iter() {
+            let find =
+                |text: &str, pattern: &str| algorithm.find_all(text.as_bytes(), pattern.as_bytes());
+            assert_eq!(find("abracadabra", "abra"), vec![0, 7]);
+            // Overlapping matches
+            assert_eq!(find("aaaa", "aa"), vec![0, 1, 2]);
+            assert_eq!(find("abababa", "aba"), vec![0, 2, 4]);
+            assert_eq!(find("abc", "d"), vec![]);
+            assert_eq!(find("ab", "abc"), vec![]);
+            assert_eq!(find("ab", ""), vec![0, 1, 2]);
+            assert_eq!(find("", ""), vec![0]);
+            assert_eq!(find("", "a"), vec![]);
+
+            let chars: Vec<char> = "çà et là, çà".chars().collect();
+            assert_eq!(algorithm.find_all(&chars, &['ç', 'à']), vec![0, 10]);
+        }
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(prefix_function(b"aabaaab"), vec![0, 1, 0, 1, 2, 2, 3]);
+        assert_eq!(z_array(b"aabxaab"), vec![7, 1, 0, 0, 3, 1, 0]);
+        assert_eq!(z_array::<u8>(&[]), vec![]);
+    }
+
+    #[test]
+    fn random_texts() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..300 {
+            // A small alphabet gives many partial and overlapping matches
+            let text: Vec<u8> = (0..rng.gen_range(0..200))
+                .map(|_| rng.gen_range(b'a'..b'c'))
+                .collect();
+            let pattern: Vec<u8> = (0..rng.gen_range(0..6))
+                .map(|_| rng.gen_range(b'a'..b'c'))
+                .collect();
+            let expected = naive_search(&text, &pattern);
+            for algorithm in ALGORITHMS.iter() {
+                assert_eq!(algorithm.find_all(&text, &pattern), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+/// What the chapter 1 string algorithms consider to be a single character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Unit {
+    /// UTF-8 bytes, so a multi-byte character counts as several characters
+    Byte,
+    /// Unicode scalar values
+    #[default]
+    Char,
+    /// User perceived characters, approximated by [graphemes]
+    Grapheme,
+}
+
+/// # Text comparison options
+///
+/// Picks the [Unit] strings are split into and how they are normalised before being compared:
+/// case folding (using the lowercase mapping of every character), and ignoring whitespace or
+/// punctuation. By default strings are compared `char` by `char`, as they are.
+///
+/// There is no canonical composition, so `"é"` and `"e\u{301}"` are different even as graphemes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextOptions {
+    unit: Unit,
+    fold_case: bool,
+    ignore_whitespace: bool,
+    ignore_punctuation: bool,
+}
+
+impl TextOptions {
+    pub fn new(unit: Unit) -> Self {
+        Self {
+            unit,
+            ..Self::default()
+        }
+    }
+
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    pub fn with_case_folding(self) -> Self {
+        Self {
+            fold_case: true,
+            ..self
+        }
+    }
+
+    pub fn ignoring_whitespace(self) -> Self {
+        Self {
+            ignore_whitespace: true,
+            ..self
+        }
+    }
+
+    pub fn ignoring_punctuation(self) -> Self {
+        Self {
+            ignore_punctuation: true,
+            ..self
+        }
+    }
+
+    /// Applies the case folding and drops the ignored characters
+    pub fn normalize(&self, s: &str) -> String {
+        let kept = s.chars().filter(|&c| {
+            !(self.ignore_whitespace && c.is_whitespace()
+                || self.ignore_punctuation && is_punctuation(c))
+        });
+        if self.fold_case {
+            kept.flat_map(char::to_lowercase).collect()
+        } else {
+            kept.collect()
+        }
+    }
+
+    /// Splits an already normalised string into units
+    pub fn split<'a>(&self, s: &'a str) -> Vec<&'a [u8]> {
+        match self.unit {
+            Unit::Byte => s.as_bytes().chunks(1).collect(),
+            Unit::Char => s
+                .char_indices()
+                .map(|(idx, c)| &s.as_bytes()[idx..idx + c.len_utf8()])
+                .collect(),
+            Unit::Grapheme => graphemes(s).map(str::as_bytes).collect(),
+        }
+    }
+
+    /// Normalised units of `s`, as their UTF-8 bytes
+    pub fn units(&self, s: &str) -> Vec<Vec<u8>> {
+        let normalized = self.normalize(s);
+        self.split(&normalized)
+            .into_iter()
+            .map(<[u8]>::to_vec)
+            .collect()
+    }
+}
+
+/// ASCII punctuation and the most common Unicode punctuation (general punctuation, CJK
+/// punctuation, inverted marks and guillemets)
+pub fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c,
+            '\u{A1}' | '\u{A7}' | '\u{AB}' | '\u{B6}' | '\u{B7}' | '\u{BB}' | '\u{BF}'
+            | '\u{2010}'..='\u{2027}'
+            | '\u{2030}'..='\u{205E}'
+            | '\u{3001}'..='\u{3003}'
+            | '\u{3008}'..='\u{3011}'
+        )
+}
+
+/// Characters which never start a grapheme cluster: combining marks, variation selectors, emoji
+/// skin tone modifiers and tags.
+fn is_extend(c: char) -> bool {
+    matches!(c,
+        '\u{300}'..='\u{36F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200C}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}'
+        | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// # Grapheme clusters
+///
+/// Hand rolled approximation of the Unicode segmentation rules, which keeps together:
+/// - `\r\n`
+/// - a character and the combining marks, variation selectors and emoji modifiers following it
+/// - emoji joined with a zero width joiner
+/// - pairs of regional indicators (flags)
+///
+/// Hangul syllables made of separate jamos and the rarer rules are not handled.
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { rest: s }
+}
+
+pub struct Graphemes<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chars = self.rest.char_indices().peekable();
+        let (_, first) = chars.next()?;
+        let mut end = first.len_utf8();
+        let mut previous = first;
+        // Number of regional indicators in the cluster
+        let mut indicators = is_regional_indicator(first) as usize;
+
+        while let Some(&(idx, c)) = chars.peek() {
+            let joined = if previous == '\r' {
+                c == '\n'
+            } else if is_regional_indicator(c) {
+                indicators == 1
+            } else {
+                is_extend(c) || c == ZERO_WIDTH_JOINER || previous == ZERO_WIDTH_JOINER
+            };
+            if !joined {
+                break;
+            }
+            indicators += is_regional_indicator(c) as usize;
+            end = idx + c.len_utf8();
+            previous = c;
+            chars.next();
+        }
+
+        let (cluster, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(cluster)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grapheme_clusters() {
+        let clusters: Vec<_> = graphemes("e\u{301}a\r\nb").collect();
+        assert_eq!(clusters, vec!["e\u{301}", "a", "\r\n", "b"]);
+
+        // Family emoji joined with zero width joiners, waving hand with a skin tone
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let wave = "\u{1F44B}\u{1F3FD}";
+        let text = format!("{}{}!", family, wave);
+        assert_eq!(
+            graphemes(&text).collect::<Vec<_>>(),
+            vec![family, wave, "!"]
+        );
+
+        // Three flags
+        let flags = "\u{1F1EB}\u{1F1F7}\u{1F1EF}\u{1F1F5}\u{1F1FA}\u{1F1F8}";
+        assert_eq!(graphemes(flags).count(), 3);
+        // Heart with the emoji variation selector
+        assert_eq!(graphemes("\u{2764}\u{FE0F}").count(), 1);
+        assert_eq!(graphemes("").next(), None);
+    }
+
+    #[test]
+    fn normalisation_and_units() {
+        let options = TextOptions::default()
+            .with_case_folding()
+            .ignoring_whitespace()
+            .ignoring_punctuation();
+        assert_eq!(options.normalize("¡Hola, Señor ÉTÉ!"), "holaseñorété");
+        assert_eq!(TextOptions::default().normalize("A b."), "A b.");
+
+        assert_eq!(TextOptions::new(Unit::Byte).units("é").len(), 2);
+        assert_eq!(TextOptions::new(Unit::Char).units("é").len(), 1);
+        assert_eq!(TextOptions::new(Unit::Char).units("e\u{301}").len(), 2);
+        assert_eq!(TextOptions::new(Unit::Grapheme).units("e\u{301}").len(), 1);
+        assert_eq!(
+            TextOptions::new(Unit::Grapheme).units("ab"),
+            vec![b"a".to_vec(), b"b".to_vec()]
+        );
+        assert!(is_punctuation('«') && is_punctuation('—') && !is_punctuation('é'));
+    }
+}
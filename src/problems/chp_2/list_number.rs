@@ -0,0 +1,364 @@
+use super::problem_5::sum_lists_backwards;
+use crate::structures::SinglyLinkedList;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ListNumberErr {
+    Empty,
+    InvalidDigit(char),
+}
+
+/// # Arbitrary precision integer
+///
+/// Extension of [problem 5][`super::problem_5`], the digits are stored in a [SinglyLinkedList] in
+/// reverse order (least significant digit first) which is the layout used by
+/// [`sum_lists_backwards`], so the addition of magnitudes is delegated to it directly.
+///
+/// The digits never have leading zeros (trailing nodes in the list), and zero is represented by an
+/// empty list which is never negative. This keeps the derived `PartialEq` correct.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ListNumber {
+    negative: bool,
+    digits: SinglyLinkedList<i32>,
+}
+
+impl ListNumber {
+    pub fn zero() -> Self {
+        Self {
+            negative: false,
+            digits: SinglyLinkedList::new(),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Least significant digit first
+    pub fn digits(&self) -> impl Iterator<Item = i32> {
+        self.digits.values()
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            negative: false,
+            digits: self.digits.values().collect(),
+        }
+    }
+
+    /// Builds a number out of least significant first digits, which may contain leading zeros.
+    fn from_digits(negative: bool, mut digits: Vec<i32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self {
+            negative: negative && !digits.is_empty(),
+            digits: digits.into_iter().collect(),
+        }
+    }
+
+    fn cmp_magnitude(&self, other: &Self) -> Ordering {
+        let lhs: Vec<_> = self.digits.values().collect();
+        let rhs: Vec<_> = other.digits.values().collect();
+        lhs.len()
+            .cmp(&rhs.len())
+            .then_with(|| lhs.iter().rev().cmp(rhs.iter().rev()))
+    }
+
+    /// Subtracts the magnitude of `smaller` from `self`, it must not be larger than `self`
+    fn sub_magnitude(&self, smaller: &Self) -> Vec<i32> {
+        let mut res = Vec::new();
+        let mut rhs = smaller.digits.values();
+        let mut borrow = 0;
+        for digit in self.digits.values() {
+            let mut val = digit - rhs.next().unwrap_or(0) - borrow;
+            borrow = 0;
+            if val < 0 {
+                val += 10;
+                borrow = 1;
+            }
+            res.push(val);
+        }
+        res
+    }
+
+    fn mul_magnitude(&self, other: &Self) -> Vec<i32> {
+        let lhs: Vec<_> = self.digits.values().collect();
+        let rhs: Vec<_> = other.digits.values().collect();
+        let mut res = vec![0; lhs.len() + rhs.len()];
+        for (i, l) in lhs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, r) in rhs.iter().enumerate() {
+                let val = res[i + j] + l * r + carry;
+                res[i + j] = val % 10;
+                carry = val / 10;
+            }
+            res[i + rhs.len()] += carry;
+        }
+        res
+    }
+}
+
+impl Clone for ListNumber {
+    fn clone(&self) -> Self {
+        Self {
+            negative: self.negative,
+            digits: self.digits.values().collect(),
+        }
+    }
+}
+
+impl Default for ListNumber {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl FromStr for ListNumber {
+    type Err = ListNumberErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if s.is_empty() {
+            return Err(ListNumberErr::Empty);
+        }
+        let digits = s
+            .chars()
+            .rev()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|d| d as i32)
+                    .ok_or(ListNumberErr::InvalidDigit(c))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_digits(negative, digits))
+    }
+}
+
+impl From<u128> for ListNumber {
+    fn from(mut num: u128) -> Self {
+        let mut digits = Vec::new();
+        while num > 0 {
+            digits.push((num % 10) as i32);
+            num /= 10;
+        }
+        Self::from_digits(false, digits)
+    }
+}
+
+impl From<i128> for ListNumber {
+    fn from(num: i128) -> Self {
+        let mut res = Self::from(num.unsigned_abs());
+        res.negative = num < 0;
+        res
+    }
+}
+
+impl fmt::Display for ListNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let digits: Vec<_> = self.digits.values().collect();
+        for digit in digits.iter().rev() {
+            write!(f, "{}", digit)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialOrd for ListNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ListNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.cmp_magnitude(other),
+            (true, true) => other.cmp_magnitude(self),
+        }
+    }
+}
+
+impl Neg for &ListNumber {
+    type Output = ListNumber;
+
+    fn neg(self) -> ListNumber {
+        let mut res = self.clone();
+        res.negative = !res.negative && !res.is_zero();
+        res
+    }
+}
+
+impl Neg for ListNumber {
+    type Output = ListNumber;
+
+    fn neg(self) -> ListNumber {
+        -&self
+    }
+}
+
+impl Add for &ListNumber {
+    type Output = ListNumber;
+
+    fn add(self, other: &ListNumber) -> ListNumber {
+        if self.negative == other.negative {
+            return ListNumber {
+                negative: self.negative,
+                digits: sum_lists_backwards(&self.digits, &other.digits),
+            };
+        }
+        // Signs differ, so the result takes the sign of the number with the largest magnitude
+        match self.cmp_magnitude(other) {
+            Ordering::Less => ListNumber::from_digits(other.negative, other.sub_magnitude(self)),
+            _ => ListNumber::from_digits(self.negative, self.sub_magnitude(other)),
+        }
+    }
+}
+
+impl Sub for &ListNumber {
+    type Output = ListNumber;
+
+    fn sub(self, other: &ListNumber) -> ListNumber {
+        self + &(-other)
+    }
+}
+
+impl Mul for &ListNumber {
+    type Output = ListNumber;
+
+    fn mul(self, other: &ListNumber) -> ListNumber {
+        ListNumber::from_digits(self.negative != other.negative, self.mul_magnitude(other))
+    }
+}
+
+impl Add for ListNumber {
+    type Output = ListNumber;
+
+    fn add(self, other: ListNumber) -> ListNumber {
+        &self + &other
+    }
+}
+
+impl Sub for ListNumber {
+    type Output = ListNumber;
+
+    fn sub(self, other: ListNumber) -> ListNumber {
+        &self - &other
+    }
+}
+
+impl Mul for ListNumber {
+    type Output = ListNumber;
+
+    fn mul(self, other: ListNumber) -> ListNumber {
+        &self * &other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn num(s: &str) -> ListNumber {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!(num("12345").to_string(), "12345");
+        assert_eq!(num("-0042").to_string(), "-42");
+        assert_eq!(num("+7").to_string(), "7");
+        assert_eq!(num("-0").to_string(), "0");
+        assert_eq!(num("000").to_string(), "0");
+        assert_eq!("".parse::<ListNumber>(), Err(ListNumberErr::Empty));
+        assert_eq!("-".parse::<ListNumber>(), Err(ListNumberErr::Empty));
+        assert_eq!(
+            "12a".parse::<ListNumber>(),
+            Err(ListNumberErr::InvalidDigit('a'))
+        );
+    }
+
+    #[test]
+    fn digits_are_reversed() {
+        assert_eq!(num("617").digits().collect::<Vec<_>>(), vec![7, 1, 6]);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(num("617") + num("295"), num("912"));
+        assert_eq!(num("-617") + num("295"), num("-322"));
+        assert_eq!(num("617") - num("617"), ListNumber::zero());
+        assert_eq!(num("100") - num("1"), num("99"));
+        assert_eq!(num("1") - num("100"), num("-99"));
+        assert_eq!(num("-12") * num("12"), num("-144"));
+        assert_eq!(num("-12") * num("0"), ListNumber::zero());
+        assert_eq!(
+            num("99999999999999999999999999999999999999999") * num("2"),
+            num("199999999999999999999999999999999999999998")
+        );
+    }
+
+    #[test]
+    fn ordering() {
+        let mut nums = [
+            num("10"),
+            num("-3"),
+            num("0"),
+            num("-20"),
+            num("9"),
+            num("100"),
+        ];
+        nums.sort();
+        let sorted: Vec<_> = nums.iter().map(ToString::to_string).collect();
+        assert_eq!(sorted, vec!["-20", "-3", "0", "9", "10", "100"]);
+    }
+
+    #[test]
+    fn against_u128() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..500 {
+            let a: u128 = rng.gen_range(0..u64::MAX as u128);
+            let b: u128 = rng.gen_range(0..u64::MAX as u128);
+            let (la, lb) = (ListNumber::from(a), ListNumber::from(b));
+
+            assert_eq!((&la + &lb).to_string(), (a + b).to_string());
+            assert_eq!((&la * &lb).to_string(), (a * b).to_string());
+            assert_eq!((&la - &lb).to_string(), (a as i128 - b as i128).to_string());
+            assert_eq!(la.cmp(&lb), a.cmp(&b));
+        }
+    }
+
+    #[test]
+    fn against_i128() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..500 {
+            let a: i128 = rng.gen_range(i64::MIN as i128..i64::MAX as i128);
+            let b: i128 = rng.gen_range(i64::MIN as i128..i64::MAX as i128);
+            let (la, lb) = (ListNumber::from(a), ListNumber::from(b));
+
+            assert_eq!(&la + &lb, ListNumber::from(a + b));
+            assert_eq!(&la - &lb, ListNumber::from(a - b));
+            assert_eq!(&la * &lb, ListNumber::from(a * b));
+            assert_eq!(la.cmp(&lb), a.cmp(&b));
+        }
+    }
+}
@@ -4,3 +4,5 @@ pub mod problem_3;
 pub mod problem_4;
 pub mod problem_5;
 pub mod problem_6;
+
+pub mod aggregate;
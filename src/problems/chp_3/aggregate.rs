@@ -0,0 +1,316 @@
+use std::cmp::Ordering;
+use std::ops::{Add, Rem};
+
+/// Associative operation used to combine the values of an [AggregateStack] or [AggregateQueue].
+///
+/// The operation does not need to be commutative, values are always combined in the order they
+/// were pushed (or enqueued). Any `Fn(&T, &T) -> T` closure implements it.
+pub trait Aggregate<T> {
+    fn combine(&self, a: &T, b: &T) -> T;
+}
+
+impl<T, F: Fn(&T, &T) -> T> Aggregate<T> for F {
+    fn combine(&self, a: &T, b: &T) -> T {
+        self(a, b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Min;
+
+impl<T: Ord + Clone> Aggregate<T> for Min {
+    fn combine(&self, a: &T, b: &T) -> T {
+        a.min(b).clone()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Max;
+
+impl<T: Ord + Clone> Aggregate<T> for Max {
+    fn combine(&self, a: &T, b: &T) -> T {
+        // Prefer `a` on ties, unlike `Ord::max` which prefers the second argument
+        match a.cmp(b) {
+            Ordering::Less => b.clone(),
+            _ => a.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sum;
+
+impl<T: Add<Output = T> + Clone> Aggregate<T> for Sum {
+    fn combine(&self, a: &T, b: &T) -> T {
+        a.clone() + b.clone()
+    }
+}
+
+/// Greatest common divisor, `T::default()` is used as zero so this is meant for non-negative
+/// integers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gcd;
+
+impl<T: Rem<Output = T> + Default + PartialEq + Clone> Aggregate<T> for Gcd {
+    fn combine(&self, a: &T, b: &T) -> T {
+        let (mut a, mut b) = (a.clone(), b.clone());
+        while b != T::default() {
+            let rem = a % b.clone();
+            a = b;
+            b = rem;
+        }
+        a
+    }
+}
+
+/// # Generalised [MinStack][`super::problem_2::MinStack`]
+///
+/// Each element is stored alongside the aggregate of every element below it (and itself), so the
+/// aggregate of the whole stack is always available in O(1).
+#[derive(Debug)]
+pub struct AggregateStack<T, M> {
+    stack: Vec<(T, T)>,
+    op: M,
+}
+
+impl<T: Clone, M: Aggregate<T>> AggregateStack<T, M> {
+    pub fn new(op: M) -> Self {
+        Self {
+            stack: Vec::new(),
+            op,
+        }
+    }
+
+    pub fn push(&mut self, val: T) {
+        let aggregate = match self.aggregate() {
+            Some(prev) => self.op.combine(prev, &val),
+            None => val.clone(),
+        };
+        self.stack.push((val, aggregate));
+    }
+
+    /// Same as [`push`](#method.push) but `val` is combined on the left of the current aggregate,
+    /// meaning the aggregate reads from the top of the stack to the bottom.
+    fn push_reversed(&mut self, val: T) {
+        let aggregate = match self.aggregate() {
+            Some(prev) => self.op.combine(&val, prev),
+            None => val.clone(),
+        };
+        self.stack.push((val, aggregate));
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.stack.pop().map(|(val, _)| val)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.stack.last().map(|(val, _)| val)
+    }
+
+    /// Aggregate of every element in the stack, `None` if it is empty.
+    pub fn aggregate(&self) -> Option<&T> {
+        self.stack.last().map(|(_, aggregate)| aggregate)
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+
+/// # Queue with O(1) amortised aggregate
+///
+/// Built from two [AggregateStack] the same way as
+/// [StackQueue][`super::problem_4::StackQueue`]. The dequeue stack aggregates from top to bottom,
+/// so combining it with the enqueue stack yields the aggregate in the order of the queue.
+#[derive(Debug)]
+pub struct AggregateQueue<T, M> {
+    stack_enqueue: AggregateStack<T, M>,
+    stack_dequeue: AggregateStack<T, M>,
+}
+
+impl<T: Clone, M: Aggregate<T> + Clone> AggregateQueue<T, M> {
+    pub fn new(op: M) -> Self {
+        Self {
+            stack_enqueue: AggregateStack::new(op.clone()),
+            stack_dequeue: AggregateStack::new(op),
+        }
+    }
+
+    pub fn enqueue(&mut self, val: T) {
+        self.stack_enqueue.push(val);
+    }
+
+    fn rebalance(&mut self) {
+        if self.stack_dequeue.is_empty() {
+            while let Some(val) = self.stack_enqueue.pop() {
+                self.stack_dequeue.push_reversed(val)
+            }
+        }
+    }
+
+    pub fn dequeue(&mut self) -> Option<T> {
+        self.rebalance();
+        self.stack_dequeue.pop()
+    }
+
+    pub fn peek(&mut self) -> Option<&T> {
+        self.rebalance();
+        self.stack_dequeue.peek()
+    }
+
+    /// Aggregate of every element in the queue from front to back, `None` if it is empty.
+    pub fn aggregate(&self) -> Option<T> {
+        match (
+            self.stack_dequeue.aggregate(),
+            self.stack_enqueue.aggregate(),
+        ) {
+            (Some(front), Some(back)) => Some(self.stack_dequeue.op.combine(front, back)),
+            (Some(only), None) | (None, Some(only)) => Some(only.clone()),
+            (None, None) => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack_dequeue.len() + self.stack_enqueue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Aggregate of every window of `size` consecutive values, in O(n) amortised.
+pub fn window_aggregates<T: Clone, M: Aggregate<T> + Clone>(
+    values: &[T],
+    size: usize,
+    op: M,
+) -> Vec<T> {
+    if size == 0 {
+        return Vec::new();
+    }
+    let mut queue = AggregateQueue::new(op);
+    let mut res = Vec::new();
+    for val in values {
+        queue.enqueue(val.clone());
+        if queue.len() > size {
+            queue.dequeue();
+        }
+        if queue.len() == size {
+            res.extend(queue.aggregate());
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_stack_min_max() {
+        let mut min_stack = AggregateStack::new(Min);
+        let mut max_stack = AggregateStack::new(Max);
+        for &val in [5, 2, 4, 12, 1].iter() {
+            min_stack.push(val);
+            max_stack.push(val);
+        }
+        assert_eq!(min_stack.aggregate(), Some(&1));
+        assert_eq!(max_stack.aggregate(), Some(&12));
+
+        assert_eq!(min_stack.pop(), Some(1));
+        assert_eq!(max_stack.pop(), Some(1));
+        assert_eq!(min_stack.aggregate(), Some(&2));
+        assert_eq!(max_stack.aggregate(), Some(&12));
+
+        max_stack.pop();
+        assert_eq!(max_stack.aggregate(), Some(&5));
+    }
+
+    #[test]
+    fn aggregate_stack_sum_gcd() {
+        let mut sum_stack = AggregateStack::new(Sum);
+        let mut gcd_stack = AggregateStack::new(Gcd);
+        assert_eq!(sum_stack.aggregate(), None);
+        for &val in [12u64, 18, 30, 7].iter() {
+            sum_stack.push(val);
+            gcd_stack.push(val);
+        }
+        assert_eq!(sum_stack.aggregate(), Some(&67));
+        assert_eq!(gcd_stack.aggregate(), Some(&1));
+        gcd_stack.pop();
+        assert_eq!(gcd_stack.aggregate(), Some(&6));
+        assert_eq!(gcd_stack.peek(), Some(&30));
+        assert_eq!(gcd_stack.len(), 3);
+    }
+
+    #[test]
+    fn aggregate_stack_not_copy() {
+        let mut stack = AggregateStack::new(Min);
+        stack.push(String::from("pear"));
+        stack.push(String::from("apple"));
+        stack.push(String::from("fig"));
+        assert_eq!(stack.aggregate().map(String::as_str), Some("apple"));
+    }
+
+    #[test]
+    fn aggregate_queue() {
+        let mut queue = AggregateQueue::new(Max);
+        assert_eq!(queue.aggregate(), None);
+
+        queue.enqueue(3);
+        queue.enqueue(9);
+        queue.enqueue(1);
+        assert_eq!(queue.aggregate(), Some(9));
+        assert_eq!(queue.peek(), Some(&3));
+        assert_eq!(queue.dequeue(), Some(3));
+        assert_eq!(queue.aggregate(), Some(9));
+        queue.enqueue(4);
+        assert_eq!(queue.dequeue(), Some(9));
+        assert_eq!(queue.aggregate(), Some(4));
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn aggregate_queue_keeps_order() {
+        // String concatenation is associative but not commutative
+        let concat = |a: &String, b: &String| format!("{}{}", a, b);
+        let mut queue = AggregateQueue::new(concat);
+        for s in ["a", "b", "c"].iter() {
+            queue.enqueue(s.to_string());
+        }
+        assert_eq!(queue.dequeue(), Some("a".to_string()));
+        queue.enqueue("d".to_string());
+        queue.enqueue("e".to_string());
+        assert_eq!(queue.aggregate(), Some("bcde".to_string()));
+    }
+
+    #[test]
+    fn sliding_window() {
+        let values = vec![1, 3, -1, -3, 5, 3, 6, 7];
+        assert_eq!(window_aggregates(&values, 3, Max), vec![3, 3, 5, 5, 6, 7]);
+        assert_eq!(
+            window_aggregates(&values, 3, Min),
+            vec![-1, -3, -3, -3, 3, 3]
+        );
+        assert_eq!(
+            window_aggregates(&values, 2, Sum),
+            vec![4, 2, -4, 2, 8, 9, 13]
+        );
+        assert_eq!(window_aggregates(&values, 9, Sum), vec![]);
+        assert_eq!(window_aggregates(&values, 0, Sum), vec![]);
+
+        // Compare against the naive O(n * k) approach
+        let values: Vec<u32> = (0..200).map(|i| (i * 7919) % 101).collect();
+        for size in 1..10 {
+            let expected: Vec<_> = values
+                .windows(size)
+                .map(|w| *w.iter().min().unwrap())
+                .collect();
+            assert_eq!(window_aggregates(&values, size, Min), expected);
+        }
+    }
+}
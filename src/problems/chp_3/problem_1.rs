@@ -1,37 +1,50 @@
-use std::fmt::Debug;
-
 #[derive(Debug, Clone, Copy)]
 struct StackInfo {
     start: usize,
     size: usize,
+    capacity: usize,
 }
 
+/// # Three in One
+///
+/// Any number of stacks sharing a single circular array. Each stack owns a contiguous region of
+/// `capacity` slots starting at `start`, and the regions are laid out one after the other in the
+/// order of the stacks.
+///
+/// When a stack runs out of room the following stacks are shifted by one to borrow a slot from the
+/// first stack with free space, and once every stack is full the backing array is doubled and the
+/// free space is split evenly between the stacks.
 pub struct MultiStack<T> {
     array: Vec<Option<T>>,
     stack_info: Vec<StackInfo>,
 }
 
-impl<T> MultiStack<T>
-where
-    T: Clone + Debug,
-{
+impl<T> MultiStack<T> {
     pub fn new(num_stack: usize, stack_capacity: usize) -> Self {
-        let array: Vec<Option<T>> = vec![None; num_stack * stack_capacity];
+        let array: Vec<Option<T>> = (0..num_stack * stack_capacity).map(|_| None).collect();
         let mut stack_info: Vec<StackInfo> = Vec::with_capacity(num_stack);
 
         for n in 0..num_stack {
             stack_info.push(StackInfo {
                 start: n * stack_capacity,
                 size: 0,
+                capacity: stack_capacity,
             });
         }
 
-        return Self { array, stack_info };
+        Self { array, stack_info }
     }
 
     pub fn push(&mut self, stack: usize, value: T) -> Result<(), &'static str> {
-        if self.stack_at_capacity(stack) {
-            self.grow(stack)?;
+        if stack >= self.stack_info.len() {
+            return Err("Stack does not exist");
+        }
+        // After an expansion the stack may still get no free slot when there are more stacks than
+        // free slots, but then the other stacks have room to lend
+        while self.stack_at_capacity(stack) {
+            if self.grow(stack).is_err() {
+                self.expand();
+            }
         }
         let cur_info = &self.stack_info[stack];
         let index = self.rebase_index(cur_info.start + cur_info.size);
@@ -41,117 +54,203 @@ where
     }
 
     pub fn pop(&mut self, stack: usize) -> Option<T> {
+        if self.is_empty(stack) {
+            return None;
+        }
         let cur_info = &self.stack_info[stack];
-        if !self.is_empty(stack) {
-            let index = self.rebase_index(cur_info.start + cur_info.size - 1);
-            let out = self.array[index].take();
-            self.stack_info[stack].size -= 1;
-            return out;
+        let index = self.rebase_index(cur_info.start + cur_info.size - 1);
+        let out = self.array[index].take();
+        self.stack_info[stack].size -= 1;
+        out
+    }
+
+    pub fn peek(&self, stack: usize) -> Option<&T> {
+        if self.is_empty(stack) {
+            return None;
         }
-        None
+        let cur_info = &self.stack_info[stack];
+        let index = self.rebase_index(cur_info.start + cur_info.size - 1);
+        self.array[index].as_ref()
     }
 
-    fn rebase_index(&self, index: usize) -> usize {
-        index % self.array.len()
+    /// Iterates over the elements of `stack` from the top to the bottom.
+    pub fn iter(&self, stack: usize) -> Iter<'_, T> {
+        let (start, remaining) = self
+            .stack_info
+            .get(stack)
+            .map_or((0, 0), |info| (info.start, info.size));
+        Iter {
+            array: &self.array,
+            start,
+            remaining,
+        }
+    }
+
+    /// Number of elements in `stack`, `0` if the stack does not exist.
+    pub fn len(&self, stack: usize) -> usize {
+        self.stack_info.get(stack).map_or(0, |info| info.size)
+    }
+
+    pub fn num_stacks(&self) -> usize {
+        self.stack_info.len()
+    }
+
+    /// Size of the shared backing array.
+    pub fn capacity(&self) -> usize {
+        self.array.len()
+    }
+
+    /// Adds a new empty stack after the existing ones and returns its index. The new stack starts
+    /// without any room and borrows it from the other stacks on the first push.
+    pub fn add_stack(&mut self) -> usize {
+        let info = match self.stack_info.last() {
+            Some(last) => StackInfo {
+                start: (last.start + last.capacity) % self.array.len().max(1),
+                size: 0,
+                capacity: 0,
+            },
+            // The array is not owned by anyone, so the new stack takes all of it
+            None => StackInfo {
+                start: 0,
+                size: 0,
+                capacity: self.array.len(),
+            },
+        };
+        self.stack_info.push(info);
+        self.stack_info.len() - 1
     }
 
-    fn get_stack(&self, stack: usize) -> StackInfo {
-        self.stack_info[stack % self.stack_info.len()]
+    /// Removes `stack` returning its elements from bottom to top. Its room is given to the
+    /// previous stack, and the index of every following stack is shifted down by one.
+    pub fn remove_stack(&mut self, stack: usize) -> Result<Vec<T>, &'static str> {
+        if stack >= self.stack_info.len() {
+            return Err("Stack does not exist");
+        }
+        let info = self.stack_info.remove(stack);
+        let values = (0..info.size)
+            .filter_map(|i| {
+                let index = self.rebase_index(info.start + i);
+                self.array[index].take()
+            })
+            .collect();
+
+        if !self.stack_info.is_empty() {
+            // Regions are contiguous, so the previous region (circularly) ends at `info.start`
+            let prev = (stack + self.stack_info.len() - 1) % self.stack_info.len();
+            self.stack_info[prev].capacity += info.capacity;
+        }
+        Ok(values)
+    }
+
+    /// Splits the free space of the array evenly between the stacks.
+    pub fn rebalance(&mut self) {
+        self.relayout(self.array.len());
+    }
+
+    fn rebase_index(&self, index: usize) -> usize {
+        index % self.array.len()
     }
 
     fn stack_at_capacity(&self, stack: usize) -> bool {
-        let cur_stack = self.get_stack(stack);
-        let next_stack = self.get_stack(stack + 1);
-        self.rebase_index(cur_stack.start + cur_stack.size) == next_stack.start
+        let cur_stack = self.stack_info[stack];
+        cur_stack.size == cur_stack.capacity
     }
 
+    /// Whether pushing to `stack` requires expanding the array, `false` if the stack does not
+    /// exist.
     pub fn is_full(&self, stack: usize) -> bool {
-        if !self.stack_at_capacity(stack) {
+        if stack >= self.stack_info.len() || !self.stack_at_capacity(stack) {
             return false;
         }
-
-        for i in 0..self.stack_info.len() {
-            let cur_stack = self.get_stack(i);
-            let next_stack = self.get_stack(i + 1);
-            if self.rebase_index(cur_stack.start + cur_stack.size) != next_stack.start {
-                return false;
-            }
-        }
-        true
+        (0..self.stack_info.len()).all(|i| self.stack_at_capacity(i))
     }
 
     pub fn is_empty(&self, stack: usize) -> bool {
-        self.stack_info[stack].size == 0
+        self.len(stack) == 0
     }
 
-    fn increment_pointer(pointer: &mut usize, len: usize) {
-        if *pointer == len - 1 {
-            *pointer = 0;
-        } else {
-            *pointer += 1;
+    /// Moves every element of `stack` one slot to the right, the slot after its top must be free.
+    fn shift_right(&mut self, stack: usize) {
+        let info = self.stack_info[stack];
+        for i in (0..info.size).rev() {
+            let from = self.rebase_index(info.start + i);
+            let to = self.rebase_index(info.start + i + 1);
+            self.array[to] = self.array[from].take();
         }
+        self.stack_info[stack].start = self.rebase_index(info.start + 1);
     }
 
+    /// Borrows a slot for `stack` from the first following stack which is not at capacity.
     fn grow(&mut self, stack: usize) -> Result<(), &'static str> {
-        let (ahead, behind): (Vec<usize>, Vec<usize>) = self
-            .stack_info
-            .iter()
-            .enumerate()
-            .map(|(i, _)| i)
-            .filter(|i| *i != stack)
-            .partition(|i| *i > stack);
-
-        let iter = ahead.iter().chain(behind.iter());
-
-        let mut rev_idx: Option<usize> = None;
-
-        for (i, &stack_index) in iter.enumerate() {
-            let info = self.get_stack(stack_index);
-            let next_stack = self.get_stack(stack_index + 1);
-            if self.rebase_index(info.start + info.size) != next_stack.start {
-                rev_idx = Some(i);
-                break;
-            }
+        let num_stack = self.stack_info.len();
+        let donor = (1..num_stack)
+            .map(|offset| (stack + offset) % num_stack)
+            .find(|&i| !self.stack_at_capacity(i))
+            .ok_or("Stacks are all full")?;
+
+        // Shift from the donor back towards `stack` so the freed slot moves down the array
+        let mut index = donor;
+        while index != stack {
+            self.shift_right(index);
+            index = (index + num_stack - 1) % num_stack;
         }
+        self.stack_info[donor].capacity -= 1;
+        self.stack_info[stack].capacity += 1;
+        Ok(())
+    }
 
-        if rev_idx.is_none() {
-            return Err("Stacks are all full");
-        }
+    /// Doubles the size of the backing array.
+    fn expand(&mut self) {
+        let new_len = (self.array.len() * 2).max(1);
+        self.relayout(new_len);
+    }
 
-        let rev_idx = rev_idx.unwrap();
+    /// Rebuilds the backing array with `new_len` slots, giving every stack its current elements
+    /// plus an even share of the free space.
+    fn relayout(&mut self, new_len: usize) {
+        let num_stack = self.stack_info.len();
+        if num_stack == 0 {
+            self.array = (0..new_len).map(|_| None).collect();
+            return;
+        }
+        let used: usize = self.stack_info.iter().map(|info| info.size).sum();
+        let free = new_len - used;
+        let mut array: Vec<Option<T>> = (0..new_len).map(|_| None).collect();
+
+        let mut start = 0;
+        for stack in 0..num_stack {
+            let info = self.stack_info[stack];
+            for i in 0..info.size {
+                let index = self.rebase_index(info.start + i);
+                array[start + i] = self.array[index].take();
+            }
+            let extra = free / num_stack + usize::from(stack < free % num_stack);
+            self.stack_info[stack] = StackInfo {
+                start,
+                size: info.size,
+                capacity: info.size + extra,
+            };
+            start += info.size + extra;
+        }
+        self.array = array;
+    }
+}
 
-        let resize_array: Vec<usize> = ahead
-            .iter()
-            .chain(behind.iter())
-            .enumerate()
-            .filter(|(i, _)| i <= &rev_idx)
-            .map(|(_, s)| *s)
-            .collect();
+pub struct Iter<'a, T> {
+    array: &'a [Option<T>],
+    start: usize,
+    remaining: usize,
+}
 
-        let mut iter = resize_array.iter();
-
-        while let Some(&index) = iter.next_back() {
-            let info = self.get_stack(index);
-
-            match (info.size, info.start + info.size < self.array.len()) {
-                (0, _) => {}
-                (_, true) => self.array[info.start..=(info.start + info.size)].rotate_right(1),
-                (_, false) => {
-                    let rebased = self.rebase_index(info.start + info.size);
-                    if rebased > 0 {
-                        self.array[..rebased].rotate_right(1)
-                    }
-                    if self.array.len() - info.start > 1 {
-                        self.array[info.start..].rotate_right(1)
-                    }
-                    self.array.swap(0, info.start);
-                }
-            }
-            let mut inner_start = self.stack_info[index].start;
-            Self::increment_pointer(&mut inner_start, self.array.len());
-            self.stack_info[index].start = inner_start;
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
         }
-        Ok(())
+        self.remaining -= 1;
+        let index = (self.start + self.remaining) % self.array.len();
+        self.array[index].as_ref()
     }
 }
 
@@ -184,10 +283,6 @@ mod tests {
         assert!(multi_stack.is_full(0));
         assert!(multi_stack.is_full(1));
 
-        assert!(multi_stack.push(0, 10).is_err());
-        assert!(multi_stack.push(1, 10).is_err());
-        assert!(multi_stack.push(2, 10).is_err());
-
         assert_eq!(multi_stack.pop(0), Some(3));
         assert_eq!(multi_stack.pop(1), Some(6));
         assert_eq!(multi_stack.pop(2), Some(9));
@@ -230,7 +325,9 @@ mod tests {
         assert!(multi_stack.push(0, 9).is_ok());
         assert!(multi_stack.is_full(0));
 
-        assert!(multi_stack.push(0, 10).is_err());
+        assert!(multi_stack.push(0, 10).is_ok());
+        assert_eq!(multi_stack.capacity(), 18);
+        assert_eq!(multi_stack.pop(0), Some(10));
         assert_eq!(multi_stack.pop(0), Some(9));
         assert_eq!(multi_stack.pop(0), Some(8));
 
@@ -241,4 +338,123 @@ mod tests {
         assert_eq!(multi_stack.pop(1), Some(10));
         assert_eq!(multi_stack.pop(2), Some(11));
     }
+
+    #[test]
+    fn grows_when_full() {
+        let mut multi_stack = MultiStack::new(3, 2);
+        for i in 0..20 {
+            assert!(multi_stack.push(i % 3, i).is_ok());
+        }
+        assert_eq!(multi_stack.capacity(), 24);
+        assert_eq!(multi_stack.len(0), 7);
+        assert_eq!(multi_stack.len(1), 7);
+        assert_eq!(multi_stack.len(2), 6);
+        assert_eq!(
+            multi_stack.iter(2).copied().collect::<Vec<_>>(),
+            vec![17, 14, 11, 8, 5, 2]
+        );
+
+        let mut empty = MultiStack::new(2, 0);
+        empty.add_stack();
+        assert!(empty.push(1, 'a').is_ok());
+        assert_eq!(empty.peek(1), Some(&'a'));
+        assert_eq!(empty.capacity(), 1);
+        assert!(empty.push(3, 'b').is_err());
+    }
+
+    #[test]
+    fn peek_len_and_iter() {
+        let mut multi_stack = MultiStack::new(2, 4);
+        assert_eq!(multi_stack.peek(0), None);
+        assert_eq!(multi_stack.iter(0).next(), None);
+        assert_eq!(multi_stack.iter(5).next(), None);
+
+        for i in 1..=6 {
+            assert!(multi_stack.push(0, i).is_ok());
+        }
+        assert!(multi_stack.push(1, 7).is_ok());
+        assert_eq!(multi_stack.peek(0), Some(&6));
+        assert_eq!(multi_stack.peek(1), Some(&7));
+        assert_eq!(multi_stack.len(0), 6);
+        assert_eq!(multi_stack.len(1), 1);
+        let values: Vec<_> = multi_stack.iter(0).copied().collect();
+        assert_eq!(values, vec![6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn add_and_remove_stacks() {
+        let mut multi_stack = MultiStack::new(2, 2);
+        assert!(multi_stack.push(0, 1).is_ok());
+        assert!(multi_stack.push(1, 2).is_ok());
+
+        let stack = multi_stack.add_stack();
+        assert_eq!(stack, 2);
+        assert!(multi_stack.push(stack, 3).is_ok());
+        assert!(multi_stack.push(stack, 4).is_ok());
+        assert_eq!(multi_stack.capacity(), 4);
+        assert!(multi_stack.is_full(0));
+
+        assert_eq!(multi_stack.remove_stack(1), Ok(vec![2]));
+        assert_eq!(multi_stack.num_stacks(), 2);
+        assert_eq!(multi_stack.iter(1).copied().collect::<Vec<_>>(), vec![4, 3]);
+        assert!(multi_stack.push(0, 5).is_ok());
+        assert_eq!(multi_stack.capacity(), 4);
+        assert_eq!(multi_stack.pop(0), Some(5));
+        assert_eq!(multi_stack.pop(0), Some(1));
+
+        assert_eq!(multi_stack.remove_stack(0), Ok(vec![]));
+        assert_eq!(multi_stack.remove_stack(0), Ok(vec![3, 4]));
+        assert!(multi_stack.remove_stack(0).is_err());
+
+        let stack = multi_stack.add_stack();
+        for i in 0..4 {
+            assert!(multi_stack.push(stack, i).is_ok());
+        }
+        assert_eq!(multi_stack.capacity(), 4);
+    }
+
+    #[test]
+    fn push_after_expanding_with_more_stacks_than_slots() {
+        let mut multi_stack = MultiStack::new(1, 1);
+        multi_stack.add_stack();
+        multi_stack.add_stack();
+        assert!(multi_stack.push(0, 'a').is_ok());
+        assert!(multi_stack.push(2, 'b').is_ok());
+        assert!(multi_stack.push(0, 'c').is_ok());
+        assert_eq!(
+            multi_stack.iter(0).copied().collect::<Vec<_>>(),
+            vec!['c', 'a']
+        );
+        assert_eq!(multi_stack.iter(2).copied().collect::<Vec<_>>(), vec!['b']);
+        assert!(multi_stack.is_empty(1));
+        assert!(!multi_stack.is_full(3));
+    }
+
+    #[test]
+    fn rebalance() {
+        let mut multi_stack = MultiStack::new(2, 3);
+        for i in 0..5 {
+            assert!(multi_stack.push(0, i).is_ok());
+        }
+        multi_stack.pop(0);
+        multi_stack.pop(0);
+        multi_stack.rebalance();
+        assert!(multi_stack.push(1, 10).is_ok());
+        assert_eq!(
+            multi_stack.iter(0).copied().collect::<Vec<_>>(),
+            vec![2, 1, 0]
+        );
+        assert_eq!(multi_stack.pop(1), Some(10));
+    }
+
+    #[test]
+    fn no_clone_bound() {
+        struct NotClone(u32);
+
+        let mut multi_stack = MultiStack::new(2, 1);
+        assert!(multi_stack.push(0, NotClone(1)).is_ok());
+        assert!(multi_stack.push(0, NotClone(2)).is_ok());
+        assert!(multi_stack.push(0, NotClone(3)).is_ok());
+        assert_eq!(multi_stack.pop(0).map(|v| v.0), Some(3));
+    }
 }
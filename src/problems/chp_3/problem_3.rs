@@ -1,9 +1,16 @@
 use std::fmt::Debug;
 
+/// Identifier of a sub-stack, unlike its index it does not change when an earlier sub-stack is
+/// removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StackId(usize);
+
 #[derive(Debug)]
 pub struct SetOfStacks<T> {
-    stacks: Vec<Vec<T>>,
+    stacks: Vec<(StackId, Vec<T>)>,
     capacity: usize,
+    rollover: bool,
+    next_id: usize,
 }
 
 impl<T> SetOfStacks<T> {
@@ -12,6 +19,18 @@ impl<T> SetOfStacks<T> {
         Self {
             stacks: Vec::new(),
             capacity,
+            rollover: false,
+            next_id: 0,
+        }
+    }
+
+    /// Same as [`new`](#method.new), but [`pop_at`](#method.pop_at) shifts the bottom element of
+    /// every following stack to the left, so every stack but the last one is always full and
+    /// only the last stack is ever removed.
+    pub fn with_rollover(capacity: usize) -> Self {
+        Self {
+            rollover: true,
+            ..Self::new(capacity)
         }
     }
 
@@ -22,18 +41,20 @@ impl<T> SetOfStacks<T> {
         })
     }
 
-    fn get_push_stack<'a>(&'a mut self) -> &'a mut Vec<T> {
+    fn get_push_stack(&mut self) -> &mut Vec<T> {
         match self.stacks.last() {
-            Some(last) if last.len() != last.capacity() => {}
+            Some((_, last)) if last.len() != self.capacity => {}
             _ => {
-                self.stacks.push(Vec::with_capacity(self.capacity));
+                let id = StackId(self.next_id);
+                self.next_id += 1;
+                self.stacks.push((id, Vec::with_capacity(self.capacity)));
             }
         };
-        self.stacks.last_mut().unwrap()
+        &mut self.stacks.last_mut().unwrap().1
     }
 
     fn get_pop_stack(&mut self) -> Option<&mut Vec<T>> {
-        self.stacks.last_mut()
+        self.stacks.last_mut().map(|(_, stack)| stack)
     }
 
     pub fn push(&mut self, val: T) {
@@ -44,20 +65,95 @@ impl<T> SetOfStacks<T> {
         let stack = self.get_pop_stack()?;
         let out = stack.pop();
         // if empty remove last
-        if stack.len() == 0 {
+        if stack.is_empty() {
             self.stacks.pop();
         }
         out
     }
 
     pub fn pop_at(&mut self, stack_idx: usize) -> Option<T> {
-        let stack = self.stacks.get_mut(stack_idx)?;
+        let (_, stack) = self.stacks.get_mut(stack_idx)?;
         let out = stack.pop();
-        if stack.len() == 0 {
+        if self.rollover {
+            for idx in stack_idx + 1..self.stacks.len() {
+                let bottom = self.stacks[idx].1.remove(0);
+                self.stacks[idx - 1].1.push(bottom);
+            }
+            if self.stacks.last().is_some_and(|(_, last)| last.is_empty()) {
+                self.stacks.pop();
+            }
+        } else if self.stacks[stack_idx].1.is_empty() {
             self.stacks.remove(stack_idx);
         }
         out
     }
+
+    pub fn pop_at_id(&mut self, id: StackId) -> Option<T> {
+        let idx = self.index_of(id)?;
+        self.pop_at(idx)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.stacks.last().and_then(|(_, stack)| stack.last())
+    }
+
+    pub fn peek_at(&self, stack_idx: usize) -> Option<&T> {
+        self.stacks
+            .get(stack_idx)
+            .and_then(|(_, stack)| stack.last())
+    }
+
+    pub fn peek_at_id(&self, id: StackId) -> Option<&T> {
+        self.peek_at(self.index_of(id)?)
+    }
+
+    /// Current index of the stack identified by `id`, `None` if it has been removed.
+    pub fn index_of(&self, id: StackId) -> Option<usize> {
+        self.stacks.iter().position(|(stack_id, _)| *stack_id == id)
+    }
+
+    pub fn stack_id(&self, stack_idx: usize) -> Option<StackId> {
+        self.stacks.get(stack_idx).map(|(id, _)| *id)
+    }
+
+    /// Total number of elements across all stacks
+    pub fn len(&self) -> usize {
+        self.stacks.iter().map(|(_, stack)| stack.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
+    pub fn num_stacks(&self) -> usize {
+        self.stacks.len()
+    }
+
+    /// Iterates over all the elements in the order they would be popped.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stacks: self.stacks.iter(),
+            inner: [].iter(),
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    stacks: std::slice::Iter<'a, (StackId, Vec<T>)>,
+    inner: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(val) = self.inner.next_back() {
+                return Some(val);
+            }
+            let (_, stack) = self.stacks.next_back()?;
+            self.inner = stack.iter();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -134,4 +230,48 @@ mod tests {
 
         assert_eq!(set_of_stacks.pop(), None);
     }
+
+    #[test]
+    fn set_of_stacks_rollover() {
+        let iter = vec![5, 2, 4, 12, 10, 6, 3, 1, 14];
+        let mut set_of_stacks = SetOfStacks::with_rollover(3);
+        for val in iter {
+            set_of_stacks.push(val);
+        }
+        let last_id = set_of_stacks.stack_id(2).unwrap();
+
+        assert_eq!(set_of_stacks.pop_at(0), Some(4));
+        // [[5, 2, 12], [10, 6, 3], [1, 14]]
+        assert_eq!(set_of_stacks.peek_at(0), Some(&12));
+        assert_eq!(set_of_stacks.peek_at(1), Some(&3));
+        assert_eq!(set_of_stacks.pop_at(1), Some(3));
+        assert_eq!(set_of_stacks.pop_at(1), Some(1));
+        // [[5, 2, 12], [10, 6, 14]]
+        assert_eq!(set_of_stacks.num_stacks(), 2);
+        assert_eq!(set_of_stacks.index_of(last_id), None);
+        assert_eq!(set_of_stacks.len(), 6);
+
+        let values: Vec<_> = set_of_stacks.iter().copied().collect();
+        assert_eq!(values, vec![14, 6, 10, 12, 2, 5]);
+
+        set_of_stacks.push(7);
+        assert_eq!(set_of_stacks.num_stacks(), 3);
+        assert_eq!(set_of_stacks.peek(), Some(&7));
+    }
+
+    #[test]
+    fn set_of_stacks_stable_ids() {
+        let mut set_of_stacks = SetOfStacks::from_iter(1..=6, 2);
+        let first = set_of_stacks.stack_id(0).unwrap();
+        let last = set_of_stacks.stack_id(2).unwrap();
+
+        assert_eq!(set_of_stacks.pop_at_id(first), Some(2));
+        assert_eq!(set_of_stacks.pop_at_id(first), Some(1));
+        assert_eq!(set_of_stacks.pop_at_id(first), None);
+        // The last stack moved from index 2 to 1, but its id did not change
+        assert_eq!(set_of_stacks.index_of(last), Some(1));
+        assert_eq!(set_of_stacks.peek_at_id(last), Some(&6));
+        assert_eq!(set_of_stacks.len(), 4);
+        assert_eq!(set_of_stacks.iter().count(), 4);
+    }
 }
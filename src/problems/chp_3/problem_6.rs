@@ -1,57 +1,104 @@
-#[allow(dead_code)]
-use std::collections::VecDeque;
-use std::ops::Deref;
-use std::time::Instant;
+use std::collections::{BTreeMap, VecDeque};
 
-#[derive(Debug)]
-pub enum Pet {
-    Cat(PetInfo),
-    Dog(PetInfo),
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pet {
+    name: String,
+    kind: String,
 }
 
-impl Deref for Pet {
-    type Target = PetInfo;
-    fn deref(&self) -> &Self::Target {
-        match self {
-            Pet::Cat(info) => info,
-            Pet::Dog(info) => info,
+impl Pet {
+    pub fn new(name: &str, kind: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: kind.to_string(),
         }
     }
+
+    pub fn new_cat(name: &str) -> Self {
+        Self::new(name, "cat")
+    }
+
+    pub fn new_dog(name: &str) -> Self {
+        Self::new(name, "dog")
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
 }
 
-#[derive(Debug)]
-pub struct PetInfo {
-    name: String,
-    timestamp: Instant,
+/// Oldest pet of a kind, as seen by an [AdoptionPolicy]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate<'a> {
+    pub kind: &'a str,
+    /// Arrival order in the shelter, lower is older
+    pub arrival: u64,
 }
 
-#[derive(Debug)]
-pub struct PetShop {
-    cats: VecDeque<Pet>,
-    dogs: VecDeque<Pet>,
+/// Picks the kind of pet to adopt out of the oldest pet of every kind currently in the shelter,
+/// the oldest pet of the chosen kind is then adopted.
+pub trait AdoptionPolicy {
+    fn choose<'a>(&self, candidates: &[Candidate<'a>]) -> Option<&'a str>;
 }
 
-impl Pet {
-    pub fn new_cat(name: &str) -> Self {
-        Self::Cat(PetInfo {
-            name: name.to_string(),
-            timestamp: Instant::now(),
-        })
+/// Oldest pet in the shelter, whatever its kind
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OldestAny;
+
+impl AdoptionPolicy for OldestAny {
+    fn choose<'a>(&self, candidates: &[Candidate<'a>]) -> Option<&'a str> {
+        candidates
+            .iter()
+            .min_by_key(|candidate| candidate.arrival)
+            .map(|candidate| candidate.kind)
     }
+}
 
-    pub fn new_dog(name: &str) -> Self {
-        Self::Dog(PetInfo {
-            name: name.to_string(),
-            timestamp: Instant::now(),
-        })
+/// Oldest pet of the given kind
+#[derive(Debug, Clone)]
+pub struct OldestOf(pub String);
+
+impl AdoptionPolicy for OldestOf {
+    fn choose<'a>(&self, candidates: &[Candidate<'a>]) -> Option<&'a str> {
+        candidates
+            .iter()
+            .find(|candidate| candidate.kind == self.0)
+            .map(|candidate| candidate.kind)
     }
 }
 
+/// Kinds in order of preference, the oldest pet of the first kind available is adopted.
+#[derive(Debug, Clone)]
+pub struct Preferences(pub Vec<String>);
+
+impl AdoptionPolicy for Preferences {
+    fn choose<'a>(&self, candidates: &[Candidate<'a>]) -> Option<&'a str> {
+        self.0
+            .iter()
+            .find_map(|kind| OldestOf(kind.clone()).choose(candidates))
+    }
+}
+
+/// # Animal shelter
+///
+/// Each kind of pet has its own queue, and the arrival order across kinds is kept with a sequence
+/// number assigned when a pet is enqueued, so the order does not depend on when the pets were
+/// created.
+#[derive(Debug)]
+pub struct PetShop {
+    pets: BTreeMap<String, VecDeque<(u64, Pet)>>,
+    next_arrival: u64,
+}
+
 impl PetShop {
     pub fn new() -> Self {
         Self {
-            cats: VecDeque::new(),
-            dogs: VecDeque::new(),
+            pets: BTreeMap::new(),
+            next_arrival: 0,
         }
     }
 
@@ -64,32 +111,57 @@ impl PetShop {
     }
 
     pub fn enqueue(&mut self, pet: Pet) {
-        match pet {
-            Pet::Cat(_) => self.cats.push_back(pet),
-            Pet::Dog(_) => self.dogs.push_back(pet),
-        }
+        let arrival = self.next_arrival;
+        self.next_arrival += 1;
+        self.pets
+            .entry(pet.kind.clone())
+            .or_default()
+            .push_back((arrival, pet));
+    }
+
+    /// Adopts the oldest pet of the kind chosen by `policy`
+    pub fn adopt<P: AdoptionPolicy + ?Sized>(&mut self, policy: &P) -> Option<Pet> {
+        let candidates: Vec<_> = self
+            .pets
+            .iter()
+            .filter_map(|(kind, queue)| {
+                queue.front().map(|(arrival, _)| Candidate {
+                    kind,
+                    arrival: *arrival,
+                })
+            })
+            .collect();
+        let kind = policy.choose(&candidates)?.to_string();
+        self.dequeue_kind(&kind)
     }
 
     pub fn dequeue_any(&mut self) -> Option<Pet> {
-        match (self.cats.front(), self.dogs.front()) {
-            (None, Some(_)) => self.dogs.pop_front(),
-            (Some(_), None) => self.cats.pop_front(),
-            (Some(Pet::Cat(cat)), Some(Pet::Dog(dog))) => {
-                if cat.timestamp > dog.timestamp {
-                    return self.dogs.pop_front();
-                }
-                self.cats.pop_front()
-            }
-            _ => None,
+        self.adopt(&OldestAny)
+    }
+
+    pub fn dequeue_kind(&mut self, kind: &str) -> Option<Pet> {
+        let queue = self.pets.get_mut(kind)?;
+        let (_, pet) = queue.pop_front()?;
+        if queue.is_empty() {
+            self.pets.remove(kind);
         }
+        Some(pet)
     }
 
     pub fn dequeue_dog(&mut self) -> Option<Pet> {
-        self.dogs.pop_front()
+        self.dequeue_kind("dog")
     }
 
     pub fn dequeue_cat(&mut self) -> Option<Pet> {
-        self.cats.pop_front()
+        self.dequeue_kind("cat")
+    }
+
+    pub fn len(&self) -> usize {
+        self.pets.values().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pets.is_empty()
     }
 }
 
@@ -119,4 +191,41 @@ mod tests {
         assert!(pet_shop.dequeue_dog().is_none());
         assert!(pet_shop.dequeue_any().is_none());
     }
+
+    #[test]
+    fn pet_shop_arrival_order() {
+        // Created in the opposite order they arrive at the shelter
+        let dog = Pet::new_dog("dog1");
+        let cat = Pet::new_cat("cat1");
+        let mut pet_shop = PetShop::new();
+        pet_shop.enqueue(cat);
+        pet_shop.enqueue(dog);
+        assert_eq!(pet_shop.len(), 2);
+        assert_eq!(pet_shop.dequeue_any().unwrap().name(), "cat1");
+        assert_eq!(pet_shop.dequeue_any().unwrap().name(), "dog1");
+        assert!(pet_shop.is_empty());
+    }
+
+    #[test]
+    fn pet_shop_policies() {
+        let mut pet_shop = PetShop::from_iter(vec![
+            Pet::new("tweety", "bird"),
+            Pet::new_dog("rex"),
+            Pet::new("nemo", "fish"),
+            Pet::new_cat("tom"),
+            Pet::new("rio", "bird"),
+        ]);
+        let preferences = Preferences(vec!["cat".to_string(), "fish".to_string()]);
+
+        assert_eq!(pet_shop.adopt(&preferences).unwrap().name(), "tom");
+        assert_eq!(pet_shop.adopt(&preferences).unwrap().name(), "nemo");
+        assert!(pet_shop.adopt(&preferences).is_none());
+        assert!(pet_shop.adopt(&OldestOf("hamster".to_string())).is_none());
+
+        let bird = pet_shop.adopt(&OldestOf("bird".to_string())).unwrap();
+        assert_eq!((bird.name(), bird.kind()), ("tweety", "bird"));
+        assert_eq!(pet_shop.adopt(&OldestAny).unwrap().name(), "rex");
+        assert_eq!(pet_shop.dequeue_kind("bird").unwrap().name(), "rio");
+        assert!(pet_shop.is_empty());
+    }
 }
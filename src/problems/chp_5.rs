@@ -6,3 +6,6 @@ pub mod problem_5;
 pub mod problem_6;
 pub mod problem_7;
 pub mod problem_8;
+
+pub mod bit_int;
+pub mod bitset;
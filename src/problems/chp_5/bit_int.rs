@@ -0,0 +1,95 @@
+use std::fmt::Debug;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
+/// Primitive integers the chapter 5 algorithms are generic over.
+///
+/// Signed integers are treated as their two's complement bit pattern, except for the right shift
+/// which is arithmetic, so the algorithms mask the bits shifted in from the left when it matters.
+pub trait BitInt:
+    Copy
+    + Eq
+    + Debug
+    + Not<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+{
+    const BITS: u32;
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn count_ones(self) -> u32;
+
+    fn wrapping_sub(self, other: Self) -> Self;
+
+    fn get_bit(self, position: u32) -> bool {
+        (self >> position) & Self::ONE != Self::ZERO
+    }
+
+    /// Every even bit set (ie. `0b0101_0101` for a `u8`)
+    fn even_bits() -> Self {
+        (0..Self::BITS)
+            .step_by(2)
+            .fold(Self::ZERO, |mask, position| mask | (Self::ONE << position))
+    }
+
+    /// Every odd bit set (ie. `0b1010_1010` for a `u8`)
+    fn odd_bits() -> Self {
+        !Self::even_bits()
+    }
+
+    /// `count` lowest bits set, `count` must be smaller than the width of the integer.
+    fn low_bits(count: u32) -> Self {
+        (Self::ONE << count).wrapping_sub(Self::ONE)
+    }
+}
+
+macro_rules! impl_bit_int {
+    ($($int:ty),*) => {
+        $(
+            impl BitInt for $int {
+                const BITS: u32 = <$int>::BITS;
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn count_ones(self) -> u32 {
+                    <$int>::count_ones(self)
+                }
+
+                fn wrapping_sub(self, other: Self) -> Self {
+                    <$int>::wrapping_sub(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_bit_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_derived_masks() {
+        assert_eq!(u8::even_bits(), 0b0101_0101);
+        assert_eq!(u8::odd_bits(), 0b1010_1010);
+        assert_eq!(u32::odd_bits(), 0xAAAA_AAAA);
+        assert_eq!(u128::even_bits(), u128::MAX / 3);
+        assert_eq!(i8::odd_bits(), 0b1010_1010u8 as i8);
+        assert_eq!(u16::low_bits(0), 0);
+        assert_eq!(u16::low_bits(15), 0x7FFF);
+        assert_eq!(i64::low_bits(63), i64::MAX);
+    }
+
+    #[test]
+    fn get_bit() {
+        assert!(6u8.get_bit(1) && 6u8.get_bit(2));
+        assert!(!6u8.get_bit(0) && !6u8.get_bit(7));
+        assert!((-1i8).get_bit(7));
+        assert!(!i128::MAX.get_bit(127));
+        assert!(i128::MIN.get_bit(127));
+    }
+}
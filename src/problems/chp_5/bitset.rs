@@ -0,0 +1,358 @@
+use std::fmt;
+use std::iter::FromIterator;
+
+const WORD: usize = 64;
+
+/// # Arbitrarily wide bit vector
+///
+/// Generalisation of the [problem 1][`super::problem_1`] helpers to any number of bits, stored
+/// as little endian `u64` words (bit 0 is the lowest bit of the first word).
+///
+/// The words never end with a zero word, so an empty set has no words at all. This keeps the
+/// derived `PartialEq` and `Hash` correct no matter how the set was built.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self { words: Vec::new() }
+    }
+
+    /// Removes the zero words at the end
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+
+    pub fn test(&self, bit: usize) -> bool {
+        self.words
+            .get(bit / WORD)
+            .is_some_and(|word| word & (1 << (bit % WORD)) != 0)
+    }
+
+    pub fn set(&mut self, bit: usize) {
+        let idx = bit / WORD;
+        if idx >= self.words.len() {
+            self.words.resize(idx + 1, 0);
+        }
+        self.words[idx] |= 1 << (bit % WORD);
+    }
+
+    pub fn clear(&mut self, bit: usize) {
+        if let Some(word) = self.words.get_mut(bit / WORD) {
+            *word &= !(1 << (bit % WORD));
+            self.trim();
+        }
+    }
+
+    pub fn toggle(&mut self, bit: usize) {
+        if self.test(bit) {
+            self.clear(bit)
+        } else {
+            self.set(bit)
+        }
+    }
+
+    /// Clears every bit from `i` to `j` (inclusive)
+    pub fn clear_range(&mut self, i: usize, j: usize) {
+        assert!(i <= j, "i should not be larger than j");
+        let end = j.saturating_add(1).min(self.words.len() * WORD);
+        let mut bit = i;
+        while bit < end {
+            let offset = bit % WORD;
+            let count = (WORD - offset).min(end - bit);
+            let mask = if count == WORD {
+                !0
+            } else {
+                ((1u64 << count) - 1) << offset
+            };
+            self.words[bit / WORD] &= !mask;
+            bit += count;
+        }
+        self.trim();
+    }
+
+    /// Generalised [`bit_num_insertion`][`super::problem_1::bit_num_insertion`], replaces bits `i`
+    /// to `j` (inclusive) with `value`.
+    ///
+    /// Panics if `value` does not fit in `j - i + 1` bits.
+    pub fn insert_range(&mut self, value: &BitSet, i: usize, j: usize) {
+        assert!(i <= j, "i should not be larger than j");
+        assert!(
+            value.highest_bit().is_none_or(|bit| bit <= j - i),
+            "value does not fit between i and j"
+        );
+        self.clear_range(i, j);
+        for bit in value.iter() {
+            self.set(bit + i);
+        }
+    }
+
+    /// Position of the highest set bit, `None` if no bit is set.
+    pub fn highest_bit(&self) -> Option<usize> {
+        let last = self.words.last()?;
+        Some((self.words.len() - 1) * WORD + (WORD - 1 - last.leading_zeros() as usize))
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Whether no bit is set
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Set bits in increasing order
+    pub fn iter(&self) -> Ones<'_> {
+        Ones {
+            words: self.words.iter(),
+            current: 0,
+            end: 0,
+        }
+    }
+
+    fn zip_words<F: Fn(u64, u64) -> u64>(&self, other: &Self, op: F) -> Self {
+        let len = self.words.len().max(other.words.len());
+        let word = |words: &[u64], idx: usize| words.get(idx).copied().unwrap_or(0);
+        let mut res = Self {
+            words: (0..len)
+                .map(|idx| op(word(&self.words, idx), word(&other.words, idx)))
+                .collect(),
+        };
+        res.trim();
+        res
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a | b)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a & b)
+    }
+
+    /// Bits set in `self` but not in `other`
+    pub fn difference(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a & !b)
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a ^ b)
+    }
+
+    /// Number of set bits strictly below `bit`
+    pub fn rank(&self, bit: usize) -> usize {
+        let idx = bit / WORD;
+        let full: usize = self
+            .words
+            .iter()
+            .take(idx)
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        let partial = match self.words.get(idx) {
+            Some(word) => (word & ((1u64 << (bit % WORD)) - 1)).count_ones() as usize,
+            None => 0,
+        };
+        full + partial
+    }
+
+    /// Position of the `n`th set bit (starting from 0), `None` if fewer bits are set.
+    pub fn select(&self, mut n: usize) -> Option<usize> {
+        for (idx, &word) in self.words.iter().enumerate() {
+            let ones = word.count_ones() as usize;
+            if n >= ones {
+                n -= ones;
+                continue;
+            }
+            let mut word = word;
+            for _ in 0..n {
+                // Clear the lowest set bit
+                word &= word - 1;
+            }
+            return Some(idx * WORD + word.trailing_zeros() as usize);
+        }
+        None
+    }
+
+    /// Value of the set if it fits in a `u128`
+    pub fn to_u128(&self) -> Option<u128> {
+        match self.words.as_slice() {
+            [] => Some(0),
+            [low] => Some(*low as u128),
+            [low, high] => Some(*low as u128 | (*high as u128) << WORD),
+            _ => None,
+        }
+    }
+}
+
+impl From<u32> for BitSet {
+    fn from(num: u32) -> Self {
+        Self::from(num as u128)
+    }
+}
+
+impl From<u64> for BitSet {
+    fn from(num: u64) -> Self {
+        Self::from(num as u128)
+    }
+}
+
+impl From<u128> for BitSet {
+    fn from(num: u128) -> Self {
+        let mut res = Self {
+            words: vec![num as u64, (num >> WORD) as u64],
+        };
+        res.trim();
+        res
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for bit in iter {
+            res.set(bit);
+        }
+        res
+    }
+}
+
+/// Binary representation with the highest bit first, like the integer types
+impl fmt::Binary for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits: String = match self.highest_bit() {
+            Some(highest) => (0..=highest)
+                .rev()
+                .map(|bit| if self.test(bit) { '1' } else { '0' })
+                .collect(),
+            None => String::from("0"),
+        };
+        f.pad_integral(true, "0b", &digits)
+    }
+}
+
+pub struct Ones<'a> {
+    words: std::slice::Iter<'a, u64>,
+    /// Bits of the current word which have not been returned yet
+    current: u64,
+    /// Position right after the last bit of the current word
+    end: usize,
+}
+
+impl Iterator for Ones<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current == 0 {
+            self.current = *self.words.next()?;
+            self.end += WORD;
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        // Clear the lowest set bit
+        self.current &= self.current - 1;
+        Some(self.end - WORD + bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn set_clear_toggle() {
+        let mut bits = BitSet::new();
+        bits.set(3);
+        bits.set(200);
+        assert!(bits.test(3) && bits.test(200));
+        assert!(!bits.test(4) && !bits.test(1000));
+        assert_eq!(bits.count_ones(), 2);
+        assert_eq!(bits.highest_bit(), Some(200));
+
+        bits.toggle(200);
+        bits.toggle(64);
+        assert_eq!(bits.iter().collect::<Vec<_>>(), vec![3, 64]);
+        bits.clear(64);
+        bits.clear(3);
+        assert!(bits.is_empty());
+        // Trailing words are trimmed so sets with the same bits are equal
+        assert_eq!(bits, BitSet::new());
+        assert_eq!(format!("{:#b}", BitSet::from(6u32)), "0b110");
+    }
+
+    #[test]
+    fn insert_range() {
+        // Example from the ctci book, same as bit_num_insertion_1
+        let mut n = BitSet::from(0b10000000000u32);
+        n.insert_range(&BitSet::from(0b10011u32), 2, 6);
+        assert_eq!(n, BitSet::from(0b10001001100u32));
+
+        // Across word boundaries, overwriting the bits already set
+        let mut n: BitSet = (0..200).collect();
+        let m: BitSet = [0, 99].iter().copied().collect();
+        n.insert_range(&m, 60, 159);
+        let expected: BitSet = (0..200).filter(|&b| b <= 60 || b >= 159).collect();
+        assert_eq!(n, expected);
+
+        // Clearing up to the end
+        n.clear_range(100, usize::MAX);
+        let expected: BitSet = (0..=60).collect();
+        assert_eq!(n, expected);
+        n.insert_range(&BitSet::from(1u32), 0, usize::MAX);
+        assert_eq!(n, BitSet::from(1u32));
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_range_too_small() {
+        let mut n = BitSet::from(1u32);
+        n.insert_range(&BitSet::from(0b10011u32), 2, 5);
+    }
+
+    #[test]
+    fn rank_select() {
+        let bits: BitSet = [1, 5, 64, 70, 300].iter().copied().collect();
+        assert_eq!(bits.rank(0), 0);
+        assert_eq!(bits.rank(2), 1);
+        assert_eq!(bits.rank(64), 2);
+        assert_eq!(bits.rank(65), 3);
+        assert_eq!(bits.rank(1000), 5);
+        assert_eq!(bits.select(0), Some(1));
+        assert_eq!(bits.select(3), Some(70));
+        assert_eq!(bits.select(4), Some(300));
+        assert_eq!(bits.select(5), None);
+        for n in 0..5 {
+            assert_eq!(bits.rank(bits.select(n).unwrap()), n);
+        }
+    }
+
+    #[test]
+    fn against_u128() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..500 {
+            let a: u128 = rng.gen();
+            let b: u128 = rng.gen::<u128>() >> rng.gen_range(0..128);
+            let (sa, sb) = (BitSet::from(a), BitSet::from(b));
+
+            assert_eq!(sa.union(&sb).to_u128(), Some(a | b));
+            assert_eq!(sa.intersection(&sb).to_u128(), Some(a & b));
+            assert_eq!(sa.difference(&sb).to_u128(), Some(a & !b));
+            assert_eq!(sa.symmetric_difference(&sb).to_u128(), Some(a ^ b));
+            assert_eq!(sa.count_ones(), a.count_ones() as usize);
+            assert_eq!(format!("{:b}", sb), format!("{:b}", b));
+
+            let bit = rng.gen_range(0..128);
+            assert_eq!(sa.test(bit), a & (1 << bit) != 0);
+            assert_eq!(sa.rank(bit), (a & ((1 << bit) - 1)).count_ones() as usize);
+            let ones: Vec<_> = (0..128).filter(|bit| a & (1 << bit) != 0).collect();
+            assert_eq!(sa.iter().collect::<Vec<_>>(), ones);
+        }
+    }
+}
@@ -1,32 +1,196 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum BinStrErr {
+    /// Only values in [0, 1) can be converted
     OutOfBounds,
+    /// More digits than the requested precision are needed
     CannotRepresent,
+    /// Bases go from 2 to 36
+    InvalidBase(u32),
+    InvalidDigit(char),
+    /// The string is not of the form `0.digits`, optionally ending with repeating digits in
+    /// parentheses like `0.1(6)`
+    InvalidFormat,
+    /// The numerator or denominator does not fit in a `u128`
+    Overflow,
+}
+
+/// Original problem, the fraction must fit in 32 binary digits
+pub fn binary_to_string(num: f64) -> Result<String, BinStrErr> {
+    to_base_string(num, 2, 32)
+}
+
+/// Writes `num` in `base` using at most `precision` digits after the point, repeating digits are
+/// written in parentheses (ie. `0.1` is `0.0(0011)` in base 2).
+///
+/// The conversion is exact, every `f64` being a fraction with a power of 2 as denominator.
+pub fn to_base_string(num: f64, base: u32, precision: usize) -> Result<String, BinStrErr> {
+    Rational::from_f64(num)?.to_base_string(base, precision)
+}
+
+/// Reads a string written by [`to_base_string`], the result is rounded to the nearest `f64`.
+pub fn from_base_string(s: &str, base: u32) -> Result<f64, BinStrErr> {
+    Ok(Rational::from_base_str(s, base)?.to_f64())
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let rem = a % b;
+        a = b;
+        b = rem;
+    }
+    a
+}
+
+fn check_base(base: u32) -> Result<(), BinStrErr> {
+    if (2..=36).contains(&base) {
+        Ok(())
+    } else {
+        Err(BinStrErr::InvalidBase(base))
+    }
 }
 
-pub fn binary_to_string(mut num: f64) -> Result<String, BinStrErr> {
-    if num < 0.0 || num >= 1.0 {
-        return Err(BinStrErr::OutOfBounds);
+/// # Exact fraction in [0, 1)
+///
+/// Always stored in lowest terms so the derived `PartialEq` compares values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    numer: u128,
+    denom: u128,
+}
+
+impl Rational {
+    pub fn new(numer: u128, denom: u128) -> Result<Self, BinStrErr> {
+        if numer >= denom {
+            return Err(BinStrErr::OutOfBounds);
+        }
+        let divisor = gcd(numer, denom);
+        Ok(Self {
+            numer: numer / divisor,
+            denom: denom / divisor,
+        })
+    }
+
+    pub fn numer(&self) -> u128 {
+        self.numer
+    }
+
+    pub fn denom(&self) -> u128 {
+        self.denom
+    }
+
+    /// Exact value of `num`, which fails with [BinStrErr::Overflow] for values too small to have
+    /// their denominator fit in a `u128` (below about `2^-74`).
+    pub fn from_f64(num: f64) -> Result<Self, BinStrErr> {
+        if !(0.0..1.0).contains(&num) {
+            return Err(BinStrErr::OutOfBounds);
+        }
+        if num == 0.0 {
+            return Self::new(0, 1);
+        }
+        // Scale by 2 until the value is an integer, which is exact for floats
+        let (mut scaled, mut exponent) = (num, 0);
+        while scaled.fract() != 0.0 {
+            scaled *= 2.0;
+            exponent += 1;
+            if exponent > 127 {
+                return Err(BinStrErr::Overflow);
+            }
+        }
+        Self::new(scaled as u128, 1 << exponent)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numer as f64 / self.denom as f64
     }
 
-    let mut divisor = 0.50;
-    let mut output = String::from("0.");
+    /// Long division keeping track of the remainders, once a remainder repeats so do the digits
+    /// generated since it was first seen.
+    pub fn to_base_string(&self, base: u32, precision: usize) -> Result<String, BinStrErr> {
+        check_base(base)?;
+        let mut digits = String::new();
+        let mut seen: HashMap<u128, usize> = HashMap::new();
+        let mut remainder = self.numer;
+        while remainder != 0 {
+            if let Some(&start) = seen.get(&remainder) {
+                return Ok(format!("0.{}({})", &digits[..start], &digits[start..]));
+            }
+            if digits.len() == precision {
+                return Err(BinStrErr::CannotRepresent);
+            }
+            seen.insert(remainder, digits.len());
+            let scaled = remainder
+                .checked_mul(base as u128)
+                .ok_or(BinStrErr::Overflow)?;
+            let digit = (scaled / self.denom) as u32;
+            digits.push(std::char::from_digit(digit, base).unwrap());
+            remainder = scaled % self.denom;
+        }
+        if digits.is_empty() {
+            digits.push('0');
+        }
+        Ok(format!("0.{}", digits))
+    }
 
-    while num > 0.0 {
-        println!("{}", num);
-        if output.len() > 34 {
-            return Err(BinStrErr::CannotRepresent);
+    /// Parses `0.digits` with an optional repeating part in parentheses, like `0.1(6)`.
+    pub fn from_base_str(s: &str, base: u32) -> Result<Self, BinStrErr> {
+        check_base(base)?;
+        let fraction = s.strip_prefix("0.").ok_or(BinStrErr::InvalidFormat)?;
+        let (fixed, repeating) = match fraction.find('(') {
+            Some(open) => {
+                let repeating = fraction[open + 1..]
+                    .strip_suffix(')')
+                    .ok_or(BinStrErr::InvalidFormat)?;
+                if repeating.is_empty() {
+                    return Err(BinStrErr::InvalidFormat);
+                }
+                (&fraction[..open], repeating)
+            }
+            None => (fraction, ""),
+        };
+        if fixed.is_empty() && repeating.is_empty() {
+            return Err(BinStrErr::InvalidFormat);
         }
-        if num >= divisor {
-            output.push('1');
-            num -= divisor;
-        } else {
-            output.push('0');
+
+        // Value of the digits as an integer and base ^ number of digits
+        let parse = |digits: &str| -> Result<(u128, u128), BinStrErr> {
+            let base = base as u128;
+            digits
+                .chars()
+                .try_fold((0u128, 1u128), |(value, power), c| {
+                    let digit = c.to_digit(base as u32).ok_or(BinStrErr::InvalidDigit(c))?;
+                    let value = value.checked_mul(base).ok_or(BinStrErr::Overflow)?;
+                    let power = power.checked_mul(base).ok_or(BinStrErr::Overflow)?;
+                    Ok((value + digit as u128, power))
+                })
+        };
+        let (fixed_value, fixed_power) = parse(fixed)?;
+        if repeating.is_empty() {
+            return Self::new(fixed_value, fixed_power);
         }
-        divisor /= 2.0;
+        // 0.a(b) = (ab - a) / (base^len(a) * (base^len(b) - 1))
+        let (repeating_value, repeating_power) = parse(repeating)?;
+        let all_value = fixed_value
+            .checked_mul(repeating_power)
+            .ok_or(BinStrErr::Overflow)?
+            .checked_add(repeating_value)
+            .ok_or(BinStrErr::Overflow)?;
+        let denom = fixed_power
+            .checked_mul(repeating_power - 1)
+            .ok_or(BinStrErr::Overflow)?;
+        Self::new(all_value - fixed_value, denom)
     }
+}
 
-    Ok(output)
+/// Decimal fraction, ie. `0.125` or `0.(3)`
+impl FromStr for Rational {
+    type Err = BinStrErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_base_str(s, 10)
+    }
 }
 
 #[cfg(test)]
@@ -46,4 +210,83 @@ mod tests {
         assert_eq!(binary_to_string(-0.5), Err(BinStrErr::OutOfBounds));
         assert_eq!(binary_to_string(0.72), Err(BinStrErr::CannotRepresent));
     }
+
+    #[test]
+    fn other_bases() {
+        assert_eq!(to_base_string(0.5, 3, 10), Ok("0.(1)".to_string()));
+        assert_eq!(to_base_string(0.75, 16, 10), Ok("0.c".to_string()));
+        assert_eq!(to_base_string(0.0, 7, 10), Ok("0.0".to_string()));
+        // The double closest to 0.72 needs 52 binary digits
+        assert!(to_base_string(0.72, 2, 52).is_ok());
+        assert_eq!(to_base_string(0.5, 1, 10), Err(BinStrErr::InvalidBase(1)));
+        assert_eq!(to_base_string(0.5, 37, 10), Err(BinStrErr::InvalidBase(37)));
+        assert_eq!(to_base_string(1.0, 2, 10), Err(BinStrErr::OutOfBounds));
+        assert_eq!(to_base_string(f64::NAN, 2, 10), Err(BinStrErr::OutOfBounds));
+        assert_eq!(
+            to_base_string(f64::MIN_POSITIVE, 2, 10),
+            Err(BinStrErr::Overflow)
+        );
+    }
+
+    #[test]
+    fn repeating_fractions() {
+        let tenth: Rational = "0.1".parse().unwrap();
+        assert_eq!(tenth, Rational::new(1, 10).unwrap());
+        assert_eq!(tenth.to_base_string(2, 10), Ok("0.0(0011)".to_string()));
+        assert_eq!(tenth.to_base_string(2, 4), Err(BinStrErr::CannotRepresent));
+
+        let sixth = Rational::new(2, 12).unwrap();
+        assert_eq!(sixth.to_base_string(10, 10), Ok("0.1(6)".to_string()));
+        assert_eq!(sixth.to_base_string(6, 10), Ok("0.1".to_string()));
+        assert_eq!(
+            Rational::new(1, 7).unwrap().to_base_string(10, 10),
+            Ok("0.(142857)".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_base_strings() {
+        assert_eq!(
+            Rational::from_base_str("0.0(0011)", 2),
+            Rational::new(1, 10)
+        );
+        assert_eq!(Rational::from_base_str("0.(1)", 3), Rational::new(1, 2));
+        assert_eq!(Rational::from_base_str("0.C", 16), Rational::new(3, 4));
+        assert_eq!("0.(9)".parse::<Rational>(), Err(BinStrErr::OutOfBounds));
+        assert_eq!(from_base_string("0.101", 2), Ok(0.625));
+        assert_eq!(from_base_string("0.1(6)", 10), Ok(1.0 / 6.0));
+
+        assert_eq!(
+            "0.12a".parse::<Rational>(),
+            Err(BinStrErr::InvalidDigit('a'))
+        );
+        assert_eq!("1.5".parse::<Rational>(), Err(BinStrErr::InvalidFormat));
+        assert_eq!("0.".parse::<Rational>(), Err(BinStrErr::InvalidFormat));
+        assert_eq!("0.1()".parse::<Rational>(), Err(BinStrErr::InvalidFormat));
+        assert_eq!("0.1(2".parse::<Rational>(), Err(BinStrErr::InvalidFormat));
+        assert_eq!(
+            Rational::from_base_str(&format!("0.{}", "1".repeat(200)), 2),
+            Err(BinStrErr::Overflow)
+        );
+        // 3 * 10^38 fits in a u128, but not once the repeating part is added
+        assert_eq!(
+            format!("0.3({})", "9".repeat(38)).parse::<Rational>(),
+            Err(BinStrErr::Overflow)
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        for base in 2..=36 {
+            for &(numer, denom) in [(1, 3), (5, 8), (7, 12), (3, 10), (1, 7)].iter() {
+                let value = Rational::new(numer, denom).unwrap();
+                let s = value.to_base_string(base, 200).unwrap();
+                assert_eq!(Rational::from_base_str(&s, base), Ok(value), "{}", s);
+            }
+        }
+        for &num in [0.1, 0.5, 0.72, 0.999, 1e-10].iter() {
+            let s = to_base_string(num, 2, 200).unwrap();
+            assert_eq!(from_base_string(&s, 2), Ok(num));
+        }
+    }
 }
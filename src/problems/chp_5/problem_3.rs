@@ -1,33 +1,20 @@
-fn get_bit(num: u32, position: u8) -> bool {
-    return (num & (1 << position)) != 0;
-}
+use super::bit_int::BitInt;
 
-pub fn flip_to_win(num: u32) -> u8 {
-    let mut can_fill = true;
-    let (mut i, mut j, mut current, mut max) = (0u8, 0u8, 0u8, 0u8);
-    while j < 31 && i < 31 {
-        match (get_bit(num, j), get_bit(num, i), can_fill) {
-            (true, _, _) => {
-                current += 1;
-                max = max.max(current);
-                j += 1;
-            }
-            (false, _, true) => {
-                can_fill = false;
-                current += 1;
-                max = max.max(current);
-                j += 1;
-            }
-            (false, true, false) => i += 1,
-            (false, false, false) => {
-                current = 0;
-                can_fill = true;
-                i += 1;
-                j += 1;
-            }
+/// Tracks the length of the current run of 1's and of the run before the last 0, flipping that 0
+/// joins both runs.
+pub fn flip_to_win<T: BitInt>(num: T) -> u32 {
+    let (mut current, mut previous, mut max) = (0, 0, 1);
+    for position in 0..T::BITS {
+        if num.get_bit(position) {
+            current += 1;
+        } else {
+            previous = current;
+            current = 0;
         }
+        max = max.max(previous + current + 1);
     }
-    max
+    // There is no 0 to flip when every bit is already set
+    max.min(T::BITS)
 }
 
 #[cfg(test)]
@@ -41,5 +28,41 @@ mod tests {
         assert_eq!(flip_to_win(0b1110111001111), 7);
         assert_eq!(flip_to_win(0b000000010000000), 2);
         assert_eq!(flip_to_win(0), 1);
+        assert_eq!(flip_to_win(!0u32), 32);
+        assert_eq!(flip_to_win(0b0111_1111u8), 8);
+        assert_eq!(flip_to_win(-2i64), 64);
+        assert_eq!(flip_to_win(u128::MAX >> 1 ^ 1 << 70), 127);
+    }
+
+    fn longest_run<T: BitInt>(num: T) -> u32 {
+        let (mut current, mut max) = (0, 0);
+        for position in 0..T::BITS {
+            current = if num.get_bit(position) {
+                current + 1
+            } else {
+                0
+            };
+            max = max.max(current);
+        }
+        max
+    }
+
+    /// Flips every bit one after the other
+    fn brute_force<T: BitInt>(num: T) -> u32 {
+        (0..T::BITS)
+            .map(|position| longest_run(num | (T::ONE << position)))
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn flip_to_win_exhaustive() {
+        for num in 0..=u8::MAX {
+            assert_eq!(flip_to_win(num), brute_force(num), "{:#b}", num);
+            assert_eq!(flip_to_win(num as i8), brute_force(num), "{:#b}", num);
+        }
+        for num in 0..=u16::MAX {
+            assert_eq!(flip_to_win(num), brute_force(num), "{:#b}", num);
+        }
     }
 }
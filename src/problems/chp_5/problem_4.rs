@@ -1,15 +1,19 @@
-pub fn prev_and_next(num: u32) -> (Option<u32>, Option<u32>) {
-    return (get_next(!num).map(|res| !res), get_next(num));
+use super::bit_int::BitInt;
+
+/// Signed integers are compared by their bit pattern, as if they were unsigned.
+pub fn prev_and_next<T: BitInt>(num: T) -> (Option<T>, Option<T>) {
+    (get_next(!num).map(|res| !res), get_next(num))
 }
-fn get_next(mut num: u32) -> Option<u32> {
-    if num == 0 {
+
+fn get_next<T: BitInt>(mut num: T) -> Option<T> {
+    if num == T::ZERO {
         return None;
     }
 
     let mut zero_count = 0;
-    let mut switch_position: Option<u8> = None;
-    for position in 0..31 {
-        if (num & (1 << position)) != 0 {
+    let mut switch_position: Option<u32> = None;
+    for position in 0..T::BITS {
+        if num.get_bit(position) {
             continue;
         }
         if zero_count != position {
@@ -24,12 +28,12 @@ fn get_next(mut num: u32) -> Option<u32> {
     // subtract one since we are about to perform the switch;
     let one_count = switch_position - zero_count - 1;
     // Set the 1
-    num = num | (1 << switch_position);
+    num = num | (T::ONE << switch_position);
 
     // Clear all bottom bits from shift position
-    num = num & !((1 << switch_position) - 1);
+    num = num & !T::low_bits(switch_position);
 
-    Some(num | (1 << one_count) - 1)
+    Some(num | T::low_bits(one_count))
 }
 
 #[cfg(test)]
@@ -56,6 +60,46 @@ mod tests {
         // Binary 0b000..001 cannot have a prev
         assert_eq!(prev_and_next(1), (None, Some(0b10)));
         // Binary 0b111..110 cannot have a next
-        assert_eq!(prev_and_next(!0 - 1), (Some(!0 - 2), None));
+        assert_eq!(prev_and_next(!0u32 - 1), (Some(!0 - 2), None));
+        // The highest bit can be used
+        assert_eq!(prev_and_next(1u32 << 30), (Some(1 << 29), Some(1 << 31)));
+        assert_eq!(prev_and_next(-1i8 << 1), (Some(!0b10), None));
+    }
+
+    /// Numbers grouped by their number of 1's are already sorted, so the previous and next
+    /// numbers are the neighbours in the group.
+    fn brute_force(width: u32) -> Vec<(Option<u32>, Option<u32>)> {
+        let max = (1u32 << width) - 1;
+        let mut groups = vec![Vec::new(); width as usize + 1];
+        for num in 0..=max {
+            groups[num.count_ones() as usize].push(num);
+        }
+        let mut res = vec![(None, None); max as usize + 1];
+        for group in groups {
+            for (idx, &num) in group.iter().enumerate() {
+                let prev = idx.checked_sub(1).map(|prev| group[prev]);
+                res[num as usize] = (prev, group.get(idx + 1).copied());
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn prev_and_next_exhaustive() {
+        let expected = brute_force(8);
+        for num in 0..=u8::MAX {
+            let (prev, next) = prev_and_next(num);
+            let actual = (prev.map(u32::from), next.map(u32::from));
+            assert_eq!(actual, expected[num as usize], "{:#b}", num);
+        }
+        let expected = brute_force(16);
+        for num in 0..=u16::MAX {
+            let (prev, next) = prev_and_next(num);
+            let actual = (prev.map(u32::from), next.map(u32::from));
+            assert_eq!(actual, expected[num as usize], "{:#b}", num);
+            let (prev, next) = prev_and_next(num as i16);
+            let actual = (prev.map(|p| p as u16 as u32), next.map(|n| n as u16 as u32));
+            assert_eq!(actual, expected[num as usize], "{:#b}", num);
+        }
     }
 }
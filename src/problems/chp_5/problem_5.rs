@@ -1,10 +1,15 @@
+use super::bit_int::BitInt;
+
 /// For (num & (num - 1)) to evaluate to false, all bits must flip. This is only true when the bit
 /// pattern has 0 or 1 bits set to 1. Thus this expression checks if the number is a power of 2 or
 /// 0.
 ///
 /// ## Example: (0b00100 - 1) => (0b00011)
-pub fn bit_wut(num: i32) -> bool {
-    (num & (num - 1)) == 0
+///
+/// The subtraction wraps, so the smallest signed integer (only the sign bit set) counts as a power
+/// of 2 as well.
+pub fn bit_wut<T: BitInt>(num: T) -> bool {
+    (num & num.wrapping_sub(T::ONE)) == T::ZERO
 }
 
 #[cfg(test)]
@@ -28,5 +33,20 @@ mod tests {
         assert!(!bit_wut(134));
         assert!(!bit_wut(573));
         assert!(!bit_wut(1099));
+        assert!(bit_wut(i32::MIN));
+        assert!(!bit_wut(-1i8));
+        assert!(bit_wut(1u128 << 127));
+    }
+
+    #[test]
+    fn bit_wut_exhaustive() {
+        for num in 0..=u16::MAX {
+            let expected = num == 0 || num.is_power_of_two();
+            assert_eq!(bit_wut(num), expected, "{}", num);
+            assert_eq!(bit_wut(num as i16), expected, "{}", num);
+            if num <= u8::MAX as u16 {
+                assert_eq!(bit_wut(num as u8), expected, "{}", num);
+            }
+        }
     }
 }
@@ -1,3 +1,5 @@
+use super::bit_int::BitInt;
+
 /// Xor'ing the two numbers will only leave the bits that are different between n and m, and the
 /// number of "high" bits is equivalent to the number of swaps needed to convert the numbers from
 /// one to the other.
@@ -5,7 +7,7 @@
 /// I'm using the build in count_ones() function since it is optmized, but getting the number of
 /// high bits is constant over the length of the int or uint with a simple for loop if I had to
 /// implement it.
-pub fn count_bit_to_swap(n: u32, m: u32) -> u32 {
+pub fn count_bit_to_swap<T: BitInt>(n: T, m: T) -> u32 {
     (n ^ m).count_ones()
 }
 
@@ -19,5 +21,34 @@ mod tests {
         assert_eq!(count_bit_to_swap(0b1111, 0b0000), 4);
         assert_eq!(count_bit_to_swap(1, 1), 0);
         assert_eq!(count_bit_to_swap(0b0100_1001, 0b1001_0010), 6);
+        assert_eq!(count_bit_to_swap(-1i64, 0), 64);
+        assert_eq!(count_bit_to_swap(u128::MAX, 1), 127);
+    }
+
+    /// Flips the differing bits of `n` one at a time until it is equal to `m`
+    fn brute_force<T: BitInt>(mut n: T, m: T) -> u32 {
+        let mut swaps = 0;
+        for position in 0..T::BITS {
+            if n.get_bit(position) != m.get_bit(position) {
+                n = n ^ (T::ONE << position);
+                swaps += 1;
+            }
+        }
+        assert_eq!(n, m);
+        swaps
+    }
+
+    #[test]
+    fn count_bit_to_swap_exhaustive() {
+        for n in 0..=u8::MAX {
+            for m in 0..=u8::MAX {
+                assert_eq!(count_bit_to_swap(n, m), brute_force(n, m));
+                assert_eq!(count_bit_to_swap(n as i8, m as i8), brute_force(n, m));
+            }
+        }
+        for n in 0..=u16::MAX {
+            let m = n.rotate_left(5) ^ 0x5A5A;
+            assert_eq!(count_bit_to_swap(n, m), brute_force(n, m));
+        }
     }
 }
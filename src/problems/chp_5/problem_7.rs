@@ -1,9 +1,10 @@
-const LEFT_MASK: u32 = 0b1010_1010_1010_1010_1010_1010_1010_1010;
-const RIGHT_MASK: u32 = 0b0101_0101_0101_0101_0101_0101_0101_0101;
+use super::bit_int::BitInt;
 
-pub fn pairwise_swap(num: u32) -> u32 {
-    let right_shift = (num >> 1) & RIGHT_MASK;
-    let left_shift = (num << 1) & LEFT_MASK;
+/// The masks are derived from the width of `T`, the right mask also clears the sign bit shifted in
+/// by the arithmetic shift of signed integers.
+pub fn pairwise_swap<T: BitInt>(num: T) -> T {
+    let right_shift = (num >> 1) & T::even_bits();
+    let left_shift = (num << 1) & T::odd_bits();
 
     return right_shift | left_shift;
 }
@@ -17,6 +18,31 @@ mod tests {
         assert_eq!(pairwise_swap(0b1010), 0b0101);
         assert_eq!(pairwise_swap(0b110), 0b1001);
         assert_eq!(pairwise_swap(0), 0);
-        assert_eq!(pairwise_swap(!0), !0);
+        assert_eq!(pairwise_swap(!0u32), !0);
+        assert_eq!(pairwise_swap(i8::MIN), 0b0100_0000);
+        assert_eq!(pairwise_swap(1u128 << 126), 1 << 127);
+    }
+
+    /// Swaps the bits of every pair one at a time
+    fn brute_force<T: BitInt>(num: T) -> T {
+        let mut res = T::ZERO;
+        for position in 0..T::BITS {
+            if num.get_bit(position ^ 1) {
+                res = res | (T::ONE << position);
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn pairwise_swap_exhaustive() {
+        for num in 0..=u8::MAX {
+            assert_eq!(pairwise_swap(num), brute_force(num));
+            assert_eq!(pairwise_swap(num as i8), brute_force(num as i8));
+        }
+        for num in 0..=u16::MAX {
+            assert_eq!(pairwise_swap(num), brute_force(num));
+            assert_eq!(pairwise_swap(num as i16), brute_force(num as i16));
+        }
     }
 }
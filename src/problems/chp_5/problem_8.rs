@@ -1,11 +1,27 @@
+use crate::structures::{Encoding, NetpbmErr, NetpbmParser};
 use std::fmt::Display;
+use std::io::{self, Read, Write};
 use std::ops::{Deref, DerefMut};
 
+/// # Monochrome framebuffer
+///
+/// `N` bytes split in rows of `width` bytes, each byte holding 8 pixels with the leftmost pixel in
+/// the highest bit. Every drawing method clips what falls outside of the screen, so shapes can be
+/// partially (or entirely) off screen, which is why their coordinates are signed.
 pub struct Screen<const N: usize> {
     array: [u8; N],
     width: usize,
 }
 
+/// How the pixels of a blitted screen are combined with the pixels under it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlitMode {
+    Copy,
+    And,
+    Or,
+    Xor,
+}
+
 impl<const N: usize> Screen<N> {
     pub fn new(width: usize) -> Self {
         assert!(N % width == 0);
@@ -14,6 +30,251 @@ impl<const N: usize> Screen<N> {
             width,
         }
     }
+
+    /// Width in pixels
+    pub fn width(&self) -> usize {
+        self.width * 8
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> usize {
+        N / self.width
+    }
+
+    /// Byte index and bit mask of a pixel, `None` if it is off screen
+    fn locate(&self, x: isize, y: isize) -> Option<(usize, u8)> {
+        if x < 0 || y < 0 || x as usize >= self.width() || y as usize >= self.height() {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        Some((y * self.width + x / 8, 0b1000_0000 >> (x % 8)))
+    }
+
+    /// Whether the pixel is on, `None` if it is off screen
+    pub fn get_pixel(&self, x: isize, y: isize) -> Option<bool> {
+        self.locate(x, y)
+            .map(|(idx, mask)| self.array[idx] & mask != 0)
+    }
+
+    pub fn set_pixel(&mut self, x: isize, y: isize, on: bool) {
+        if let Some((idx, mask)) = self.locate(x, y) {
+            if on {
+                self.array[idx] |= mask;
+            } else {
+                self.array[idx] &= !mask;
+            }
+        }
+    }
+
+    /// Turns every pixel on or off
+    pub fn fill(&mut self, on: bool) {
+        let byte = if on { !0 } else { 0 };
+        self.array = [byte; N];
+    }
+
+    /// Clips the line to the screen and draws it a byte at a time with [`draw_line`]
+    pub fn horizontal_line(&mut self, x1: isize, x2: isize, y: isize) {
+        let (x1, x2) = (x1.min(x2).max(0), x1.max(x2));
+        if y < 0 || x2 < 0 {
+            return;
+        }
+        draw_line(self, x1 as usize, x2 as usize, y as usize);
+    }
+
+    pub fn vertical_line(&mut self, x: isize, y1: isize, y2: isize) {
+        let height = self.height() as isize;
+        for y in y1.min(y2).max(0)..=y1.max(y2).min(height - 1) {
+            self.set_pixel(x, y, true);
+        }
+    }
+
+    /// Bresenham's line algorithm, works in every octant by stepping on both axes with an error
+    /// term.
+    pub fn line(&mut self, x1: isize, y1: isize, x2: isize, y2: isize) {
+        let (dx, dy) = ((x2 - x1).abs(), -(y2 - y1).abs());
+        let (step_x, step_y) = ((x2 - x1).signum(), (y2 - y1).signum());
+        let (mut x, mut y) = (x1, y1);
+        let mut error = dx + dy;
+        loop {
+            self.set_pixel(x, y, true);
+            if x == x2 && y == y2 {
+                break;
+            }
+            let double = 2 * error;
+            if double >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if double <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Outline of the rectangle with its top left corner at (`x`, `y`)
+    pub fn rect(&mut self, x: isize, y: isize, width: usize, height: usize) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (right, bottom) = (x + width as isize - 1, y + height as isize - 1);
+        self.horizontal_line(x, right, y);
+        self.horizontal_line(x, right, bottom);
+        self.vertical_line(x, y, bottom);
+        self.vertical_line(right, y, bottom);
+    }
+
+    pub fn fill_rect(&mut self, x: isize, y: isize, width: usize, height: usize) {
+        if width == 0 {
+            return;
+        }
+        for row in y..y + height as isize {
+            self.horizontal_line(x, x + width as isize - 1, row);
+        }
+    }
+
+    /// Midpoint circle algorithm, calls `plot` with the two ends of the horizontal span of the
+    /// circle for every pair of mirrored octant points.
+    fn circle_spans<F: FnMut(&mut Self, isize, isize, isize)>(
+        &mut self,
+        cx: isize,
+        cy: isize,
+        radius: usize,
+        mut plot: F,
+    ) {
+        let (mut x, mut y) = (radius as isize, 0);
+        let mut error = 1 - x;
+        while x >= y {
+            plot(self, cx - x, cx + x, cy + y);
+            plot(self, cx - x, cx + x, cy - y);
+            plot(self, cx - y, cx + y, cy + x);
+            plot(self, cx - y, cx + y, cy - x);
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    pub fn circle(&mut self, cx: isize, cy: isize, radius: usize) {
+        self.circle_spans(cx, cy, radius, |screen, left, right, y| {
+            screen.set_pixel(left, y, true);
+            screen.set_pixel(right, y, true);
+        });
+    }
+
+    pub fn fill_circle(&mut self, cx: isize, cy: isize, radius: usize) {
+        self.circle_spans(cx, cy, radius, |screen, left, right, y| {
+            screen.horizontal_line(left, right, y)
+        });
+    }
+
+    /// Sets every pixel connected (horizontally or vertically) to (`x`, `y`) which has the same
+    /// value as it to `on`.
+    pub fn flood_fill(&mut self, x: isize, y: isize, on: bool) {
+        let target = match self.get_pixel(x, y) {
+            Some(value) if value != on => value,
+            _ => return,
+        };
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if self.get_pixel(x, y) != Some(target) {
+                continue;
+            }
+            self.set_pixel(x, y, on);
+            stack.extend([(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].iter());
+        }
+    }
+
+    /// Draws `src` with its top left corner at (`x`, `y`)
+    pub fn blit<const M: usize>(&mut self, src: &Screen<M>, x: isize, y: isize, mode: BlitMode) {
+        for src_y in 0..src.height() as isize {
+            for src_x in 0..src.width() as isize {
+                let (dst_x, dst_y) = (x + src_x, y + src_y);
+                let (dst, value) = match (self.get_pixel(dst_x, dst_y), src.get_pixel(src_x, src_y))
+                {
+                    (Some(dst), Some(value)) => (dst, value),
+                    _ => continue,
+                };
+                let value = match mode {
+                    BlitMode::Copy => value,
+                    BlitMode::And => dst & value,
+                    BlitMode::Or => dst | value,
+                    BlitMode::Xor => dst ^ value,
+                };
+                self.set_pixel(dst_x, dst_y, value);
+            }
+        }
+    }
+
+    /// Writes the screen as a PBM image, `1` being an "on" pixel (black).
+    pub fn write_pbm<W: Write>(&self, mut writer: W, encoding: Encoding) -> io::Result<()> {
+        match encoding {
+            Encoding::Plain => {
+                writeln!(writer, "P1\n{} {}", self.width(), self.height())?;
+                for row in self.array.chunks(self.width) {
+                    let pixels: Vec<u8> = row
+                        .iter()
+                        .flat_map(|byte| (0..8).rev().map(move |bit| b'0' + (byte >> bit & 1)))
+                        .collect();
+                    // Lines of a plain PBM should not be longer than 70 characters
+                    for line in pixels.chunks(70) {
+                        writer.write_all(line)?;
+                        writer.write_all(b"\n")?;
+                    }
+                }
+            }
+            Encoding::Binary => {
+                write!(writer, "P4\n{} {}\n", self.width(), self.height())?;
+                writer.write_all(&self.array)?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// Reads a plain or binary PBM image, which must fit exactly in the `N` bytes of the screen.
+    ///
+    /// Rows are padded to a whole number of bytes, the padding pixels being off.
+    pub fn read_pbm<R: Read>(mut reader: R) -> Result<Self, NetpbmErr> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut parser = NetpbmParser::new(&data);
+        let magic = parser.magic()?;
+        if magic != 1 && magic != 4 {
+            return Err(NetpbmErr::InvalidMagic);
+        }
+        let (width, height) = (parser.number()?, parser.number()?);
+        let row_bytes = width.div_ceil(8);
+        if row_bytes == 0 || row_bytes.checked_mul(height) != Some(N) {
+            return Err(NetpbmErr::SizeMismatch {
+                expected: (row_bytes.saturating_mul(8), N / row_bytes.max(1)),
+                found: (width, height),
+            });
+        }
+
+        let mut screen = Self::new(row_bytes);
+        if magic == 4 {
+            screen.array.copy_from_slice(parser.raster(N)?);
+            // Whatever the padding bits were, they are not part of the image
+            if width % 8 != 0 {
+                let mask = !0u8 << (8 - width % 8);
+                for row in screen.array.chunks_mut(row_bytes) {
+                    row[row_bytes - 1] &= mask;
+                }
+            }
+        } else {
+            for y in 0..height {
+                for x in 0..width {
+                    let on = parser.bit()?;
+                    screen.set_pixel(x as isize, y as isize, on);
+                }
+            }
+        }
+        Ok(screen)
+    }
 }
 
 impl<const N: usize> Deref for Screen<N> {
@@ -55,29 +316,33 @@ impl<const N: usize> Display for Screen<N> {
     }
 }
 
-pub fn draw_line<const N: usize>(
-    screen: &mut Screen<N>,
-    width: usize,
-    x1: usize,
-    x2: usize,
-    y: usize,
-) {
-    let row_start = width * y;
+/// Turns on the pixels from `x1` to `x2` (inclusive) on row `y`, setting whole bytes at once.
+///
+/// The line is clipped to the screen, the width of a row being the one of `screen`.
+pub fn draw_line<const N: usize>(screen: &mut Screen<N>, x1: usize, x2: usize, y: usize) {
+    if y >= screen.height() || x1 >= screen.width() {
+        return;
+    }
+    let x2 = x2.min(screen.width() - 1);
+    if x1 > x2 {
+        return;
+    }
+    let row_start = screen.width * y;
     let row = &mut screen[row_start + (x1 / 8)..=row_start + (x2 / 8)];
-    let first_mask = !0 >> (x1 % 8) as u8;
-    let last_mask = !(!0 >> ((x2 + 1) % 8));
+    let first_mask = !0u8 >> (x1 % 8);
+    let last_mask = !0u8 << (7 - x2 % 8);
 
     if let Some(first) = row.first_mut() {
         // Early return since x1 and xy are in the same byte
         if x2 / 8 == x1 / 8 {
-            *first = last_mask & first_mask;
+            *first |= last_mask & first_mask;
             return;
         }
-        *first = first_mask;
+        *first |= first_mask;
     }
 
     if let Some(last) = row.last_mut() {
-        *last = last_mask;
+        *last |= last_mask;
     }
 
     if row.len() > 2 {
@@ -96,7 +361,7 @@ mod tests {
     fn draw_line_1() {
         let width = 3;
         let mut screen = Screen::<6>::new(width);
-        draw_line(&mut screen, width, 8, 16, 0);
+        draw_line(&mut screen, 8, 16, 0);
         assert_eq!(screen.array, [0, 0b1111_1111, 0b1000_0000, 0, 0, 0]);
     }
 
@@ -104,10 +369,10 @@ mod tests {
     fn draw_line_2() {
         let width = 3;
         let mut screen = Screen::<6>::new(width);
-        draw_line(&mut screen, width, 8, 8, 0);
+        draw_line(&mut screen, 8, 8, 0);
         assert_eq!(screen.array, [0, 0b1000_0000, 0, 0, 0, 0]);
         let mut screen = Screen::<6>::new(width);
-        draw_line(&mut screen, width, 10, 12, 0);
+        draw_line(&mut screen, 10, 12, 0);
         assert_eq!(screen.array, [0, 0b0011_1000, 0, 0, 0, 0]);
     }
 
@@ -115,7 +380,7 @@ mod tests {
     fn draw_line_3() {
         let width = 7;
         let mut screen = Screen::<49>::new(width);
-        draw_line(&mut screen, width, 0, 56, 3);
+        draw_line(&mut screen, 0, 56, 3);
         assert_eq!(screen.array[0..7], [0; 7]);
         assert_eq!(screen.array[14..21], [0; 7]);
         assert_eq!(screen.array[21..28], [!0; 7]);
@@ -126,7 +391,7 @@ mod tests {
     fn draw_line_4() {
         let width = 16;
         let mut screen = Screen::<256>::new(width);
-        draw_line(&mut screen, width, 26, 110, 8);
+        draw_line(&mut screen, 26, 110, 8);
         assert_eq!(screen.array[0..16], [0; 16]);
         assert_eq!(
             screen.array[128..144],
@@ -151,4 +416,206 @@ mod tests {
         );
         assert_eq!(screen.array[48..64], [0; 16]);
     }
+
+    fn rows<const N: usize>(screen: &Screen<N>) -> Vec<String> {
+        (0..screen.height() as isize)
+            .map(|y| {
+                (0..screen.width() as isize)
+                    .map(|x| {
+                        if screen.get_pixel(x, y).unwrap() {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn draw_line_clipped() {
+        let mut screen = Screen::<4>::new(2);
+        // The first byte already has a pixel on, which is kept
+        screen.set_pixel(0, 0, true);
+        draw_line(&mut screen, 3, 100, 0);
+        draw_line(&mut screen, 0, 3, 2);
+        draw_line(&mut screen, 20, 30, 1);
+        screen.horizontal_line(-5, 2, 1);
+        assert_eq!(screen.array, [0b1001_1111, !0, 0b1110_0000, 0]);
+    }
+
+    #[test]
+    fn pixels() {
+        let mut screen = Screen::<4>::new(1);
+        assert_eq!((screen.width(), screen.height()), (8, 4));
+        screen.set_pixel(7, 3, true);
+        screen.set_pixel(8, 3, true);
+        screen.set_pixel(-1, 0, true);
+        assert_eq!(screen.get_pixel(7, 3), Some(true));
+        assert_eq!(screen.get_pixel(8, 3), None);
+        assert_eq!(screen.array, [0, 0, 0, 1]);
+        screen.set_pixel(7, 3, false);
+        assert_eq!(screen.array, [0; 4]);
+        screen.fill(true);
+        assert_eq!(screen.array, [!0; 4]);
+    }
+
+    #[test]
+    fn lines_and_rects() {
+        let mut screen = Screen::<6>::new(1);
+        screen.line(0, 0, 7, 3);
+        screen.line(7, 5, 0, 5);
+        screen.vertical_line(2, 3, 100);
+        assert_eq!(
+            rows(&screen),
+            vec!["##......", "..##....", "....##..", "..#...##", "..#.....", "########",]
+        );
+
+        let mut screen = Screen::<5>::new(1);
+        screen.rect(1, 0, 5, 4);
+        screen.fill_rect(-2, 4, 4, 10);
+        assert_eq!(
+            rows(&screen),
+            vec![".#####..", ".#...#..", ".#...#..", ".#####..", "##......"]
+        );
+    }
+
+    #[test]
+    fn steep_lines_in_every_direction() {
+        for &(x1, y1, x2, y2) in [(1, 0, 3, 7), (3, 7, 1, 0), (6, 1, 0, 4), (0, 4, 6, 1)].iter() {
+            let mut screen = Screen::<8>::new(1);
+            screen.line(x1, y1, x2, y2);
+            assert_eq!(screen.get_pixel(x1, y1), Some(true));
+            assert_eq!(screen.get_pixel(x2, y2), Some(true));
+            // A line has exactly one pixel per step on its longest axis
+            let count: u32 = screen.array.iter().map(|byte| byte.count_ones()).sum();
+            let steps = (x2 - x1).abs().max((y2 - y1).abs()) as u32 + 1;
+            assert_eq!(count, steps);
+        }
+    }
+
+    #[test]
+    fn circles_and_flood_fill() {
+        let mut screen = Screen::<9>::new(1);
+        screen.circle(4, 4, 3);
+        assert_eq!(
+            rows(&screen),
+            vec![
+                "........", "...###..", "..#...#.", ".#.....#", ".#.....#", ".#.....#", "..#...#.",
+                "...###..", "........",
+            ]
+        );
+        screen.flood_fill(4, 4, true);
+        let mut filled = Screen::<9>::new(1);
+        filled.fill_circle(4, 4, 3);
+        assert_eq!(screen.array, filled.array);
+
+        // Filling the outside stops at the circle, and at the edges of the screen
+        screen.flood_fill(0, 0, true);
+        assert_eq!(screen.array, [!0; 9]);
+        screen.flood_fill(0, 0, true);
+        screen.flood_fill(100, 0, false);
+        assert_eq!(screen.array, [!0; 9]);
+
+        // Partially off screen
+        let mut screen = Screen::<2>::new(1);
+        screen.fill_circle(0, 0, 1);
+        assert_eq!(rows(&screen), vec!["##......", "#......."]);
+    }
+
+    #[test]
+    fn blit() {
+        let mut sprite = Screen::<2>::new(1);
+        sprite.fill_rect(0, 0, 2, 2);
+        let mut screen = Screen::<3>::new(1);
+        screen.horizontal_line(0, 7, 1);
+
+        screen.blit(&sprite, 6, 0, BlitMode::Or);
+        assert_eq!(rows(&screen), vec!["......##", "########", "........"]);
+        screen.blit(&sprite, 6, 0, BlitMode::Xor);
+        assert_eq!(rows(&screen), vec!["........", "######..", "........"]);
+        screen.blit(&sprite, -1, 1, BlitMode::And);
+        assert_eq!(rows(&screen), vec!["........", "#.......", "........"]);
+        let mut empty = Screen::<2>::new(2);
+        empty.set_pixel(0, 0, true);
+        screen.blit(&empty, 0, 1, BlitMode::Copy);
+        assert_eq!(rows(&screen), vec!["........", "#.......", "........"]);
+    }
+
+    #[test]
+    fn pbm_fixture() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/smiley.pbm");
+        let screen = Screen::<16>::read_pbm(std::fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(screen.width(), 16);
+        assert_eq!(screen.height(), 8);
+        assert_eq!(rows(&screen)[0], ".....######.....");
+        assert_eq!(rows(&screen)[7], ".....######.....");
+
+        // The eyes are closed shapes, filling the inside of the face leaves them alone
+        let mut filled = Screen::<16>::read_pbm(std::fs::File::open(path).unwrap()).unwrap();
+        filled.flood_fill(7, 4, true);
+        assert_eq!(filled.get_pixel(0, 0), Some(false));
+        assert_eq!(filled.get_pixel(7, 4), Some(true));
+        let ones = |screen: &Screen<16>| screen.iter().map(|b| b.count_ones()).sum::<u32>();
+        assert_eq!(ones(&filled), ones(&screen) + 42);
+    }
+
+    #[test]
+    fn pbm_round_trip() {
+        let mut screen = Screen::<30>::new(3);
+        screen.circle(11, 4, 4);
+        screen.line(0, 9, 23, 0);
+        for &encoding in [Encoding::Plain, Encoding::Binary].iter() {
+            let mut image = Vec::new();
+            screen.write_pbm(&mut image, encoding).unwrap();
+            let read = Screen::<30>::read_pbm(image.as_slice()).unwrap();
+            assert_eq!(read.array, screen.array);
+            assert_eq!(read.width, 3);
+        }
+
+        let mut image = Vec::new();
+        Screen::<2>::new(1)
+            .write_pbm(&mut image, Encoding::Plain)
+            .unwrap();
+        assert_eq!(image, b"P1\n8 2\n00000000\n00000000\n");
+        let mut image = Vec::new();
+        screen.write_pbm(&mut image, Encoding::Binary).unwrap();
+        assert!(image.starts_with(b"P4\n24 10\n"));
+        assert_eq!(image.len(), 9 + 30);
+    }
+
+    #[test]
+    fn pbm_padding_and_errors() {
+        // 3 pixels wide, the padding bits of the binary rows are cleared
+        let screen = Screen::<2>::read_pbm(&b"P4 3 2\n\xFF\x40"[..]).unwrap();
+        assert_eq!(rows(&screen), vec!["###.....", ".#......"]);
+        let screen = Screen::<2>::read_pbm(&b"P1 3 2 111 010"[..]).unwrap();
+        assert_eq!(rows(&screen), vec!["###.....", ".#......"]);
+
+        assert!(matches!(
+            Screen::<2>::read_pbm(&b"P1 16 2"[..]),
+            Err(NetpbmErr::SizeMismatch { .. })
+        ));
+        assert!(matches!(
+            Screen::<2>::read_pbm(&b"P6 8 2"[..]),
+            Err(NetpbmErr::InvalidMagic)
+        ));
+        assert!(matches!(
+            Screen::<2>::read_pbm(&b"P1 8 2 0101"[..]),
+            Err(NetpbmErr::UnexpectedEof)
+        ));
+        assert!(matches!(
+            Screen::<2>::read_pbm(&b"P1 8 2 012"[..]),
+            Err(NetpbmErr::InvalidNumber)
+        ));
+        assert!(matches!(
+            Screen::<2>::read_pbm(&b"P4 16 18446744073709551615"[..]),
+            Err(NetpbmErr::SizeMismatch { .. })
+        ));
+        assert!(matches!(
+            Screen::<2>::read_pbm(&b"P4 18446744073709551615 1"[..]),
+            Err(NetpbmErr::SizeMismatch { .. })
+        ));
+    }
 }
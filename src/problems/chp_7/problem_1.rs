@@ -1,4 +1,6 @@
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use std::collections::HashSet;
 use std::fmt::Display;
 use std::io;
@@ -6,28 +8,50 @@ use std::io::prelude::*;
 use std::iter::FromIterator;
 use std::ops::{Deref, DerefMut};
 
+mod engine;
+mod strategy;
+pub use engine::*;
+pub use strategy::*;
+
+/// Terminal frontend, the rules of the game live in [GameState] and this only translates its
+/// [Event]s to text and the user's input to [Action]s.
 pub struct BlackJack {
-    pub deck: Deck,
-    pub players: Vec<Player>,
-    pub dealer: Player,
+    pub game: GameState,
 }
 
-#[derive(PartialEq, Eq)]
-enum PlayerStatus {
+const STARTING_BANKROLL: u64 = 100;
+const SHOE_DECKS: usize = 6;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PlayerStatus {
     Playing,
     Staying,
     Busted,
+    Surrendered,
 }
 
+#[derive(Debug, Clone)]
 pub struct Player {
     pub name: String,
     pub hand: Vec<Card>,
     status: PlayerStatus,
     is_dealer: bool,
+    doubled: bool,
+    from_split: bool,
+    bet: u64,
 }
 
 pub struct Deck(Vec<Card>);
 
+/// Several decks shuffled together, with a cut card placed so that the shoe is reshuffled once
+/// enough of it has been dealt.
+pub struct Shoe {
+    cards: Vec<Card>,
+    decks: usize,
+    cut_card: usize,
+    rng: StdRng,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Card {
     Ace(Suit),
@@ -47,11 +71,6 @@ pub enum Card {
 
 impl BlackJack {
     pub fn new() -> Self {
-        let mut result = Self {
-            deck: Deck::new(),
-            players: Vec::new(),
-            dealer: Player::dealer(),
-        };
         let player_number;
 
         loop {
@@ -68,165 +87,246 @@ impl BlackJack {
             }
         }
 
+        let mut names = Vec::new();
         for _ in 0..player_number {
-            result.players.push(Player::new());
+            println!("Enter player's name:");
+            let mut name = String::new();
+            io::stdin()
+                .read_line(&mut name)
+                .expect("failed to read name");
+            names.push(name.trim().to_string());
         }
-        result
+        Self::with_players(names)
     }
 
-    pub fn run(&mut self) {
-        println!(concat!(
-            "++++++++++++++++++++++++++++++++++\n",
-            "+  Black Jack Game is starting!  +\n",
-            "++++++++++++++++++++++++++++++++++\n",
-        ));
-
-        self.deck.shuffle();
+    pub fn with_players<I: IntoIterator<Item = S>, S: Into<String>>(names: I) -> Self {
+        let mut game = GameState::new(names, Shoe::new(SHOE_DECKS));
+        for seat in 0..game.num_seats() {
+            game.deposit(seat, STARTING_BANKROLL);
+        }
+        Self { game }
+    }
 
-        self.dealer.hit(self.deck.draw());
-        self.dealer.hit(self.deck.draw());
+    pub fn run(&mut self) {
+        let stdin = io::stdin();
+        self.play(&mut stdin.lock(), &mut io::stdout())
+            .expect("failed to play the round");
+    }
 
-        for player in &mut self.players {
-            player.hit(self.deck.draw());
-            player.hit(self.deck.draw());
+    /// Plays a single round reading the actions from `input` and writing the game to `output`.
+    pub fn play<R: BufRead, W: Write>(&mut self, input: &mut R, output: &mut W) -> io::Result<()> {
+        writeln!(
+            output,
+            concat!(
+                "++++++++++++++++++++++++++++++++++\n",
+                "+  Black Jack Game is starting!  +\n",
+                "++++++++++++++++++++++++++++++++++\n",
+            )
+        )?;
+
+        for seat in 0..self.game.num_seats() {
+            if !self.read_bet(seat, input, output)? {
+                writeln!(output, "Thanks for playing!")?;
+                return Ok(());
+            }
         }
 
-        for player in &mut self.players {
-            while player.status == PlayerStatus::Playing {
-                println!("\n{}\n", player.to_string());
-                print!("Available Actions: Hit (h), Stay (s), Quit (q): ");
-                io::stdout().flush().unwrap();
-
-                let mut input = String::new();
-                io::stdin()
-                    .read_line(&mut input)
-                    .expect("Could not read line");
-                input.pop();
-
-                match input.trim() {
-                    "h" | "H" => {
-                        let card = self.deck.draw();
-                        println!("{} is dealt: {}", player.name, card);
-                        let totals = player.hit(card);
-                        let min = totals.iter().min().unwrap();
-                        if min > &21 {
-                            println!("{} Busted with {}", player.name, min);
-                            player.bust();
-                        }
-                    }
-                    "s" | "S" => {
-                        println!("{} is staying at {}", player.name, player.best_total());
-                        player.stay();
-                    }
-                    "q" | "Q" => {
-                        println!("Thanks for playing!");
-                        return;
-                    }
-                    _ => {}
+        let events = self.game.deal();
+        self.write_events(output, &events)?;
+
+        while let Some(id) = self.game.turn() {
+            let actions = self.game.available_actions();
+            let prompt = actions
+                .iter()
+                .map(|action| format!("{:?} ({})", action, Self::action_key(*action)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(output, "\n{}\n", self.game.hand(id).to_string())?;
+            write!(output, "Available Actions: {}, Quit (q): ", prompt)?;
+            output.flush()?;
+
+            let mut line = String::new();
+            let key = match input.read_line(&mut line)? {
+                0 => String::from("q"),
+                _ => line.trim().to_lowercase(),
+            };
+            if key == "q" {
+                // The bets are returned so that the next round can be played
+                let events = self.game.abort_round();
+                self.write_events(output, &events)?;
+                writeln!(output, "Thanks for playing!")?;
+                return Ok(());
+            }
+            let action = actions
+                .iter()
+                .copied()
+                .find(|action| Self::action_key(*action) == key);
+            if let Some(action) = action {
+                if let Ok(events) = self.game.apply(action) {
+                    self.write_events(output, &events)?;
                 }
             }
         }
 
-        while self.dealer.status == PlayerStatus::Playing {
-            println!("\n{}\n", self.dealer.to_string());
-
-            let dealer_val = self.dealer.best_total();
-
-            if dealer_val > 21 {
-                println!("Dealer Busted!");
-                self.dealer.bust();
-            } else if dealer_val >= 17 {
-                println!("Dealer Staying!");
-                self.dealer.stay();
-                break;
-            } else {
-                let card = self.deck.draw();
-                self.dealer.hit(card);
-                println!("Dealer takes a hit: {}", card)
-            }
+        let winners: Vec<_> = self
+            .game
+            .outcomes()
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, Outcome::Win | Outcome::Blackjack))
+            .map(|(id, _)| self.game.hand(*id).name.clone())
+            .collect();
+        if winners.is_empty() {
+            writeln!(output, "The Dealer Won!")
+        } else {
+            writeln!(output, "Winners: {}", winners.join(", "))
         }
+    }
 
-        if self.dealer.status == PlayerStatus::Busted {
-            let winners: Vec<_> = self
-                .players
-                .iter()
-                .filter_map(|player| {
-                    if player.status == PlayerStatus::Staying {
-                        return Some(player.name.clone());
+    /// Prompts `seat` for its bet until a valid one is entered, returns `false` if the player quit.
+    fn read_bet<R: BufRead, W: Write>(
+        &mut self,
+        seat: usize,
+        input: &mut R,
+        output: &mut W,
+    ) -> io::Result<bool> {
+        let name = self.game.hands(seat)[0].name.clone();
+        loop {
+            write!(
+                output,
+                "{} has {} chips, enter your bet (0 to watch), Quit (q): ",
+                name,
+                self.game.bankroll(seat)
+            )?;
+            output.flush()?;
+
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 || line.trim().eq_ignore_ascii_case("q") {
+                return Ok(false);
+            }
+            match line.trim().parse() {
+                Ok(amount) => match self.game.place_bet(seat, amount) {
+                    Ok(()) => return Ok(true),
+                    Err(BetErr::InsufficientFunds) => writeln!(output, "Not enough chips")?,
+                    Err(BetErr::RoundInProgress) => {
+                        // Only when the game was driven directly and left in the middle of a round
+                        let events = self.game.abort_round();
+                        self.write_events(output, &events)?;
                     }
-                    None
-                })
-                .collect();
+                },
+                Err(_) => writeln!(output, "Invalid bet")?,
+            }
+        }
+    }
 
-            println!("Winners: {}", winners.join(", "));
-            return;
+    fn action_key(action: Action) -> &'static str {
+        match action {
+            Action::Hit => "h",
+            Action::Stay => "s",
+            Action::Double => "d",
+            Action::Split => "p",
+            Action::Surrender => "r",
         }
+    }
 
-        let dealer_val = self.dealer.best_total();
-        let winners: Vec<_> = self
-            .players
-            .iter()
-            .filter_map(|player| {
-                if player.status == PlayerStatus::Staying && player.best_total() >= dealer_val {
-                    return Some(player.name.clone());
+    fn write_events<W: Write>(&self, output: &mut W, events: &[Event]) -> io::Result<()> {
+        for event in events {
+            let name = |id: &HandId| self.game.hand(*id).name.clone();
+            match event {
+                Event::Dealt(id, card) => writeln!(output, "{} is dealt: {}", name(id), card)?,
+                Event::DealerDealt(card) => writeln!(output, "Dealer is dealt: {}", card)?,
+                Event::DealerHoleCard => writeln!(output, "Dealer is dealt a face down card")?,
+                Event::Turn(_) => {}
+                Event::Blackjack(id) => writeln!(output, "{} has a Black Jack!", name(id))?,
+                Event::Busted(id, total) => writeln!(output, "{} Busted with {}", name(id), total)?,
+                Event::Stayed(id, total) => {
+                    writeln!(output, "{} is staying at {}", name(id), total)?
                 }
-                None
-            })
-            .collect();
-        if winners.len() == 0 {
-            println!("The Dealer Won!");
-        } else {
-            println!("Winners: {}", winners.join(", "));
+                Event::Doubled(id) => writeln!(output, "{} doubles down", name(id))?,
+                Event::Split(id, _) => writeln!(output, "{} splits", name(id))?,
+                Event::Surrendered(id) => writeln!(output, "{} surrenders", name(id))?,
+                Event::DealerRevealed(card) => writeln!(output, "Dealer reveals: {}", card)?,
+                Event::DealerHit(card) => writeln!(output, "Dealer takes a hit: {}", card)?,
+                Event::DealerStayed(_) => writeln!(output, "Dealer Staying!")?,
+                Event::DealerBusted(_) => writeln!(output, "Dealer Busted!")?,
+                Event::Settled(id, outcome) => writeln!(output, "{}: {:?}", name(id), outcome)?,
+                Event::Paid(id, amount) => writeln!(
+                    output,
+                    "{} is paid {}, now has {} chips",
+                    name(id),
+                    amount,
+                    self.game.bankroll(id.seat)
+                )?,
+                Event::Shuffled => writeln!(output, "The shoe is reshuffled")?,
+            }
         }
+        Ok(())
     }
 }
 
 impl Player {
-    pub fn new() -> Self {
-        println!("Enter player's name:");
-        let mut name = String::new();
-        io::stdin()
-            .read_line(&mut name)
-            .expect("failed to read name");
-        // remove new line
-        name.pop();
+    pub fn new<S: Into<String>>(name: S) -> Self {
         Self {
-            name,
+            name: name.into(),
             hand: Vec::new(),
             status: PlayerStatus::Playing,
             is_dealer: false,
+            doubled: false,
+            from_split: false,
+            bet: 0,
         }
     }
 
     fn dealer() -> Self {
         Self {
-            name: String::from("Dealer"),
-            hand: Vec::new(),
-            status: PlayerStatus::Playing,
             is_dealer: true,
+            ..Self::new("Dealer")
         }
     }
 
-    fn dealer_total(&self) -> u8 {
-        let mut has_ace = false;
-        let mut total = 0;
-        for card in &self.hand {
-            match card {
-                Card::Ace(_) if has_ace => {
-                    total += card.val(false);
-                }
-                Card::Ace(_) if !has_ace => {
-                    has_ace = true;
-                    if total + card.val(true) <= 21 {
-                        total += card.val(true)
-                    } else {
-                        total = card.val(false);
-                    }
-                }
-                _ => total += card.val(false),
-            }
+    /// Total counting every ace as 1
+    pub fn hard_total(&self) -> u8 {
+        self.hand.iter().map(|card| card.val(false)).sum()
+    }
+
+    /// Whether one of the aces can be counted as 11 without busting
+    pub fn is_soft(&self) -> bool {
+        let has_ace = self.hand.iter().any(|card| matches!(card, Card::Ace(_)));
+        has_ace && self.hard_total() + 10 <= 21
+    }
+
+    /// Highest total that does not bust if there is one, otherwise the busted hard total.
+    pub fn total(&self) -> u8 {
+        if self.is_soft() {
+            self.hard_total() + 10
+        } else {
+            self.hard_total()
         }
-        total
+    }
+
+    /// Two card 21, hands resulting from a split do not count
+    pub fn is_blackjack(&self) -> bool {
+        self.hand.len() == 2 && self.total() == 21 && !self.from_split
+    }
+
+    pub fn is_busted(&self) -> bool {
+        self.hard_total() > 21
+    }
+
+    pub fn status(&self) -> PlayerStatus {
+        self.status
+    }
+
+    pub fn is_doubled(&self) -> bool {
+        self.doubled
+    }
+
+    /// Amount at stake on this hand, including the extra bet when doubling down
+    pub fn bet(&self) -> u64 {
+        self.bet
+    }
+
+    fn dealer_total(&self) -> u8 {
+        self.total()
     }
 
     pub fn possible_totals(&self) -> Vec<u8> {
@@ -258,7 +358,11 @@ impl Player {
     }
 
     pub fn clear(&mut self) {
-        self.hand.clear()
+        self.hand.clear();
+        self.status = PlayerStatus::Playing;
+        self.doubled = false;
+        self.from_split = false;
+        self.bet = 0;
     }
 
     pub fn hit(&mut self, card: Card) -> Vec<u8> {
@@ -274,6 +378,10 @@ impl Player {
         self.status = PlayerStatus::Staying
     }
 
+    fn surrender(&mut self) {
+        self.status = PlayerStatus::Surrendered
+    }
+
     pub fn to_string(&self) -> String {
         if self.is_dealer {
             return self.dealer_to_string();
@@ -343,9 +451,21 @@ impl Deck {
         Self(cards)
     }
 
+    /// Builds a deck which draws `cards` in the given order, mostly useful to script games.
+    pub fn from_cards<I: IntoIterator<Item = Card>>(cards: I) -> Self {
+        let mut cards: Vec<_> = cards.into_iter().collect();
+        cards.reverse();
+        Self(cards)
+    }
+
     pub fn shuffle(&mut self) {
         let mut rng = rand::thread_rng();
-        self.0.shuffle(&mut rng);
+        self.shuffle_with(&mut rng);
+    }
+
+    /// Shuffles using the given `rng`, seeding it makes the order of the cards reproducible.
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.0.shuffle(rng);
     }
 
     /// Add a new deck when we run out, this is done for simplicity, but if we wanted a more robust
@@ -360,6 +480,84 @@ impl Deck {
     }
 }
 
+impl Shoe {
+    /// Shuffled shoe of `decks` decks, with the cut card placed after 75% of the cards
+    pub fn new(decks: usize) -> Self {
+        Self::with_rng(decks, StdRng::from_entropy())
+    }
+
+    /// Same as [`new`](#method.new) but every shuffle of the shoe is reproducible
+    pub fn seeded(decks: usize, seed: u64) -> Self {
+        Self::with_rng(decks, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(decks: usize, rng: StdRng) -> Self {
+        assert!(decks != 0, "a shoe needs at least one deck");
+        let mut shoe = Self {
+            cards: Vec::new(),
+            decks,
+            cut_card: 0,
+            rng,
+        };
+        shoe.reshuffle();
+        shoe.with_penetration(0.75)
+    }
+
+    /// Places the cut card so that `penetration` (between 0 and 1) of the shoe is dealt before
+    /// reshuffling.
+    pub fn with_penetration(mut self, penetration: f64) -> Self {
+        let total = self.decks * 52;
+        let dealt = (total as f64 * penetration.clamp(0.0, 1.0)) as usize;
+        self.cut_card = total - dealt;
+        self
+    }
+
+    pub fn decks(&self) -> usize {
+        self.decks
+    }
+
+    /// Cards left to be drawn
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Whether the cut card has been reached, the shoe should be reshuffled before the next round
+    pub fn cut_card_reached(&self) -> bool {
+        self.cards.len() <= self.cut_card
+    }
+
+    /// Puts every card back in the shoe and shuffles it
+    pub fn reshuffle(&mut self) {
+        self.cards = (0..self.decks).flat_map(|_| Deck::new().0).collect();
+        self.cards.shuffle(&mut self.rng);
+    }
+
+    /// Draws the next card, the shoe is reshuffled if it runs out in the middle of a round.
+    pub fn draw(&mut self) -> Card {
+        if self.cards.is_empty() {
+            self.reshuffle();
+        }
+        self.cards.pop().unwrap()
+    }
+}
+
+/// Single deck shoe which deals the cards of `deck` in order, the cut card is at the very end so
+/// it is only reshuffled once every card has been dealt.
+impl From<Deck> for Shoe {
+    fn from(deck: Deck) -> Self {
+        Self {
+            cards: deck.0,
+            decks: 1,
+            cut_card: 0,
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
 impl Deref for Deck {
     type Target = Vec<Card>;
 
@@ -464,4 +662,59 @@ mod tests {
         assert_eq!(suit, Suit::Spade);
         assert_eq!(card, Card::Ten(Suit::Heart));
     }
+
+    #[test]
+    fn terminal_frontend() {
+        let cards = [10, 6, 5, 10, 5, 13].iter().map(|&v| Card::from((v, 1)));
+        let mut game = BlackJack::with_players(vec!["Alice"]);
+        *game.game.shoe_mut() = Deck::from_cards(cards).into();
+
+        let mut input = io::Cursor::new("200\n10\nx\nh\ns\n");
+        let mut output = Vec::new();
+        game.play(&mut input, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("Alice is dealt: 5♠"));
+        assert!(output.contains("Not enough chips"));
+        assert!(output.contains("Alice is staying at 20"));
+        assert!(output.contains("Dealer Busted!"));
+        assert!(output.contains("Alice is paid 20, now has 110 chips"));
+        assert!(output.ends_with("Winners: Alice\n"));
+    }
+
+    #[test]
+    fn terminal_frontend_quit() {
+        let mut game = BlackJack::with_players(vec!["Alice"]);
+        *game.game.shoe_mut() = Deck::from_cards((2..=5).map(|v| Card::from((v, 1)))).into();
+
+        let mut output = Vec::new();
+        game.play(&mut io::Cursor::new("5\nq\n"), &mut output)
+            .unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .ends_with("Thanks for playing!\n"));
+    }
+
+    #[test]
+    fn terminal_frontend_quit_mid_round() {
+        let cards = [10, 6, 5, 10, 5, 13, 10, 6, 10, 6, 10, 10, 13];
+        let mut game = BlackJack::with_players(vec!["Alice"]);
+        *game.game.shoe_mut() = Deck::from_cards(cards.iter().map(|&v| Card::from((v, 1)))).into();
+
+        let mut output = Vec::new();
+        game.play(&mut io::Cursor::new("10\nq\n"), &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Alice is paid 10, now has 100 chips"));
+        assert_eq!(game.game.turn(), None);
+
+        // EOF quits as well, and the next round can still be played
+        let mut output = Vec::new();
+        game.play(&mut io::Cursor::new("20\n"), &mut output)
+            .unwrap();
+        assert_eq!(game.game.bankroll(0), 100);
+        game.play(&mut io::Cursor::new("10\ns\n"), &mut Vec::new())
+            .unwrap();
+        assert_eq!(game.game.bankroll(0), 110);
+    }
 }
@@ -0,0 +1,892 @@
+use super::{Card, Player, PlayerStatus, Shoe};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    Hit,
+    Stay,
+    Double,
+    Split,
+    Surrender,
+}
+
+/// Identifies a hand at the table, a seat only has more than one hand after a split.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HandId {
+    pub seat: usize,
+    pub hand: usize,
+}
+
+/// Table configuration
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rules {
+    /// The dealer stays on every 17 when `false`, and hits a soft 17 (ie. `A 6`) when `true`
+    pub dealer_hits_soft_17: bool,
+    /// Numerator and denominator of the Black Jack payout
+    pub blackjack_payout: (u64, u64),
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            dealer_hits_soft_17: false,
+            blackjack_payout: (3, 2),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome {
+    Blackjack,
+    Win,
+    Push,
+    Lose,
+    Surrender,
+}
+
+/// Everything that happened as a result of [`GameState::deal`] or [`GameState::apply`], in order.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Event {
+    Dealt(HandId, Card),
+    DealerDealt(Card),
+    /// The dealer's second card, which stays face down until [`Event::DealerRevealed`]
+    DealerHoleCard,
+    /// `HandId` is the next hand expected to act
+    Turn(HandId),
+    Blackjack(HandId),
+    Busted(HandId, u8),
+    Stayed(HandId, u8),
+    Doubled(HandId),
+    /// The second hand was split off the first one
+    Split(HandId, HandId),
+    Surrendered(HandId),
+    DealerRevealed(Card),
+    DealerHit(Card),
+    DealerStayed(u8),
+    DealerBusted(u8),
+    Settled(HandId, Outcome),
+    /// Amount returned to the bankroll of the hand's seat, including the original bet
+    Paid(HandId, u64),
+    /// The cut card was reached so the shoe was reshuffled before dealing
+    Shuffled,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ActionErr {
+    /// No hand is expected to act, a new round needs to be dealt
+    RoundOver,
+    NotAllowed(Action),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BetErr {
+    /// Bets can only be placed between rounds
+    RoundInProgress,
+    InsufficientFunds,
+}
+
+struct Seat {
+    hands: Vec<Player>,
+    bankroll: u64,
+    /// Bet placed for the next round
+    bet: u64,
+}
+
+/// # Black Jack rules engine
+///
+/// The engine never does any I/O, it consumes [Action]s for the hand whose [`turn`] it is and
+/// reports what happened through [Event]s, which makes it easy to drive from a terminal, a bot or
+/// tests. Since the [Shoe] is handed over, a seeded (or scripted) shoe gives reproducible games.
+///
+/// Rules:
+/// - The dealer checks for Black Jack right after the deal, whether it hits a soft 17 is part of
+///   the [Rules].
+/// - Doubling and splitting are allowed on any first two cards (of equal value for splits), split
+///   aces only receive one card each.
+/// - Surrendering is only allowed on the first two cards, before splitting, and returns half of
+///   the bet.
+/// - Bets are taken out of the bankroll when placed, a seat which did not bet still plays but
+///   nothing is paid out. Doubling and splitting need enough bankroll to match the bet, and
+///   payouts are rounded down to the chip.
+///
+/// [`turn`]: GameState::turn
+pub struct GameState {
+    shoe: Shoe,
+    rules: Rules,
+    seats: Vec<Seat>,
+    dealer: Player,
+    turn: Option<HandId>,
+    outcomes: Vec<(HandId, Outcome)>,
+}
+
+impl GameState {
+    pub fn new<I, S, D>(names: I, shoe: D) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+        D: Into<Shoe>,
+    {
+        Self::with_rules(names, shoe, Rules::default())
+    }
+
+    pub fn with_rules<I, S, D>(names: I, shoe: D, rules: Rules) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+        D: Into<Shoe>,
+    {
+        Self {
+            shoe: shoe.into(),
+            rules,
+            seats: names
+                .into_iter()
+                .map(|name| Seat {
+                    hands: vec![Player::new(name)],
+                    bankroll: 0,
+                    bet: 0,
+                })
+                .collect(),
+            dealer: Player::dealer(),
+            turn: None,
+            outcomes: Vec::new(),
+        }
+    }
+
+    pub fn shoe_mut(&mut self) -> &mut Shoe {
+        &mut self.shoe
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    pub fn bankroll(&self, seat: usize) -> u64 {
+        self.seats[seat].bankroll
+    }
+
+    /// Adds `amount` to the bankroll of `seat`
+    pub fn deposit(&mut self, seat: usize, amount: u64) {
+        self.seats[seat].bankroll += amount;
+    }
+
+    /// Places the bet of `seat` for the next round, replacing the one already placed if any.
+    pub fn place_bet(&mut self, seat: usize, amount: u64) -> Result<(), BetErr> {
+        if self.turn.is_some() {
+            return Err(BetErr::RoundInProgress);
+        }
+        let seat = &mut self.seats[seat];
+        if amount > seat.bankroll + seat.bet {
+            return Err(BetErr::InsufficientFunds);
+        }
+        seat.bankroll = seat.bankroll + seat.bet - amount;
+        seat.bet = amount;
+        Ok(())
+    }
+
+    pub fn num_seats(&self) -> usize {
+        self.seats.len()
+    }
+
+    /// Hands of the player at `seat`, in the order they were created
+    pub fn hands(&self, seat: usize) -> &[Player] {
+        &self.seats[seat].hands
+    }
+
+    pub fn hand(&self, id: HandId) -> &Player {
+        &self.seats[id.seat].hands[id.hand]
+    }
+
+    fn hand_mut(&mut self, id: HandId) -> &mut Player {
+        &mut self.seats[id.seat].hands[id.hand]
+    }
+
+    /// The dealer's hand, the hole card is part of it even before it is revealed
+    pub fn dealer(&self) -> &Player {
+        &self.dealer
+    }
+
+    /// The dealer's face up card
+    pub fn dealer_up_card(&self) -> Option<Card> {
+        self.dealer.hand.first().copied()
+    }
+
+    /// Hand expected to act next, `None` once the round is over
+    pub fn turn(&self) -> Option<HandId> {
+        self.turn
+    }
+
+    /// Outcome of every hand of the last finished round
+    pub fn outcomes(&self) -> &[(HandId, Outcome)] {
+        &self.outcomes
+    }
+
+    /// Clears the table and deals a new round with the bets placed so far.
+    pub fn deal(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        self.turn = None;
+        self.outcomes.clear();
+        self.dealer.clear();
+        for seat in self.seats.iter_mut() {
+            seat.hands.truncate(1);
+            seat.hands[0].clear();
+            seat.hands[0].bet = std::mem::take(&mut seat.bet);
+        }
+        if self.shoe.cut_card_reached() {
+            self.shoe.reshuffle();
+            events.push(Event::Shuffled);
+        }
+
+        for round in 0..2 {
+            for seat in 0..self.seats.len() {
+                let id = HandId { seat, hand: 0 };
+                let card = self.shoe.draw();
+                self.hand_mut(id).hit(card);
+                events.push(Event::Dealt(id, card));
+            }
+            let card = self.shoe.draw();
+            self.dealer.hit(card);
+            events.push(match round {
+                0 => Event::DealerDealt(card),
+                _ => Event::DealerHoleCard,
+            });
+        }
+
+        if self.dealer.is_blackjack() {
+            events.push(Event::DealerRevealed(self.dealer.hand[1]));
+            self.settle(&mut events);
+            return events;
+        }
+
+        for seat in 0..self.seats.len() {
+            let id = HandId { seat, hand: 0 };
+            if self.hand(id).is_blackjack() {
+                self.hand_mut(id).stay();
+                events.push(Event::Blackjack(id));
+            }
+        }
+        self.advance(HandId { seat: 0, hand: 0 }, &mut events);
+        events
+    }
+
+    /// Abandons the round in progress, for instance when the players leave the table, returning
+    /// the bet of every hand including the doubles and splits. Does nothing between rounds.
+    pub fn abort_round(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        if self.turn.take().is_none() {
+            return events;
+        }
+        for seat in 0..self.seats.len() {
+            for hand in 0..self.seats[seat].hands.len() {
+                let id = HandId { seat, hand };
+                let bet = std::mem::take(&mut self.hand_mut(id).bet);
+                if bet > 0 {
+                    self.seats[seat].bankroll += bet;
+                    events.push(Event::Paid(id, bet));
+                }
+            }
+        }
+        events
+    }
+
+    /// Actions the current hand is allowed to take
+    pub fn available_actions(&self) -> Vec<Action> {
+        let id = match self.turn {
+            Some(id) => id,
+            None => return Vec::new(),
+        };
+        let hand = self.hand(id);
+        let seat = &self.seats[id.seat];
+        let mut actions = vec![Action::Hit, Action::Stay];
+        if hand.hand.len() == 2 {
+            let can_match_bet = seat.bankroll >= hand.bet;
+            if can_match_bet {
+                actions.push(Action::Double);
+            }
+            if can_match_bet && hand.hand[0].val(false) == hand.hand[1].val(false) {
+                actions.push(Action::Split);
+            }
+            if seat.hands.len() == 1 {
+                actions.push(Action::Surrender);
+            }
+        }
+        actions
+    }
+
+    /// Plays `action` for the current hand. Once the last hand is done the dealer plays and every
+    /// hand is settled as part of the returned events.
+    pub fn apply(&mut self, action: Action) -> Result<Vec<Event>, ActionErr> {
+        let id = self.turn.ok_or(ActionErr::RoundOver)?;
+        if !self.available_actions().contains(&action) {
+            return Err(ActionErr::NotAllowed(action));
+        }
+
+        let mut events = Vec::new();
+        match action {
+            Action::Hit => {
+                self.hit(id, &mut events);
+                self.stay_on_21(id, &mut events);
+            }
+            Action::Stay => {
+                self.hand_mut(id).stay();
+                events.push(Event::Stayed(id, self.hand(id).total()));
+            }
+            Action::Double => {
+                let bet = self.hand(id).bet;
+                self.seats[id.seat].bankroll -= bet;
+                self.hand_mut(id).bet += bet;
+                self.hand_mut(id).doubled = true;
+                events.push(Event::Doubled(id));
+                self.hit(id, &mut events);
+                if !self.hand(id).is_busted() {
+                    self.hand_mut(id).stay();
+                    events.push(Event::Stayed(id, self.hand(id).total()));
+                }
+            }
+            Action::Split => self.split(id, &mut events),
+            Action::Surrender => {
+                self.hand_mut(id).surrender();
+                events.push(Event::Surrendered(id));
+            }
+        }
+        self.advance(id, &mut events);
+        Ok(events)
+    }
+
+    fn hit(&mut self, id: HandId, events: &mut Vec<Event>) {
+        let card = self.shoe.draw();
+        self.hand_mut(id).hit(card);
+        events.push(Event::Dealt(id, card));
+        if self.hand(id).is_busted() {
+            self.hand_mut(id).bust();
+            events.push(Event::Busted(id, self.hand(id).total()));
+        }
+    }
+
+    fn stay_on_21(&mut self, id: HandId, events: &mut Vec<Event>) {
+        let hand = self.hand(id);
+        if hand.status() == PlayerStatus::Playing && hand.total() == 21 {
+            self.hand_mut(id).stay();
+            events.push(Event::Stayed(id, 21));
+        }
+    }
+
+    fn split(&mut self, id: HandId, events: &mut Vec<Event>) {
+        let card = self.hand_mut(id).hand.pop().unwrap();
+        let bet = self.hand(id).bet;
+        let mut new_hand = Player::new(self.hand(id).name.clone());
+        new_hand.hand.push(card);
+        new_hand.from_split = true;
+        new_hand.bet = bet;
+        self.hand_mut(id).from_split = true;
+        let seat = &mut self.seats[id.seat];
+        seat.bankroll -= bet;
+        seat.hands.push(new_hand);
+
+        let new_id = HandId {
+            seat: id.seat,
+            hand: seat.hands.len() - 1,
+        };
+        events.push(Event::Split(id, new_id));
+
+        let aces = matches!(card, Card::Ace(_));
+        for hand_id in [id, new_id].iter().copied() {
+            let card = self.shoe.draw();
+            self.hand_mut(hand_id).hit(card);
+            events.push(Event::Dealt(hand_id, card));
+            if aces {
+                self.hand_mut(hand_id).stay();
+                events.push(Event::Stayed(hand_id, self.hand(hand_id).total()));
+            } else {
+                self.stay_on_21(hand_id, events);
+            }
+        }
+    }
+
+    /// Moves the turn to the next hand still playing starting at `current`, or plays the dealer
+    /// and settles the round if there is none left.
+    fn advance(&mut self, current: HandId, events: &mut Vec<Event>) {
+        let next = (current.seat..self.seats.len())
+            .flat_map(|seat| {
+                let first = if seat == current.seat {
+                    current.hand
+                } else {
+                    0
+                };
+                (first..self.seats[seat].hands.len()).map(move |hand| HandId { seat, hand })
+            })
+            .find(|id| self.hand(*id).status() == PlayerStatus::Playing);
+
+        match next {
+            Some(id) => {
+                if self.turn != Some(id) {
+                    events.push(Event::Turn(id));
+                }
+                self.turn = Some(id);
+            }
+            None => {
+                self.turn = None;
+                self.play_dealer(events);
+                self.settle(events);
+            }
+        }
+    }
+
+    fn play_dealer(&mut self, events: &mut Vec<Event>) {
+        events.push(Event::DealerRevealed(self.dealer.hand[1]));
+
+        let all_done = self.seats.iter().flat_map(|seat| &seat.hands).all(|hand| {
+            hand.is_blackjack()
+                || matches!(
+                    hand.status(),
+                    PlayerStatus::Busted | PlayerStatus::Surrendered
+                )
+        });
+        if all_done {
+            return;
+        }
+
+        while self.dealer_should_hit() {
+            let card = self.shoe.draw();
+            self.dealer.hit(card);
+            events.push(Event::DealerHit(card));
+        }
+        let total = self.dealer.total();
+        if self.dealer.is_busted() {
+            self.dealer.bust();
+            events.push(Event::DealerBusted(total));
+        } else {
+            self.dealer.stay();
+            events.push(Event::DealerStayed(total));
+        }
+    }
+
+    fn dealer_should_hit(&self) -> bool {
+        let total = self.dealer.total();
+        total < 17 || (total == 17 && self.dealer.is_soft() && self.rules.dealer_hits_soft_17)
+    }
+
+    /// Amount returned for a hand with the given `outcome` and `bet`, including the bet itself
+    pub fn payout(&self, outcome: Outcome, bet: u64) -> u64 {
+        let (num, den) = self.rules.blackjack_payout;
+        match outcome {
+            Outcome::Blackjack => bet + bet * num / den,
+            Outcome::Win => bet * 2,
+            Outcome::Push => bet,
+            Outcome::Surrender => bet / 2,
+            Outcome::Lose => 0,
+        }
+    }
+
+    fn settle(&mut self, events: &mut Vec<Event>) {
+        self.turn = None;
+        let dealer_total = self.dealer.total();
+        for seat in 0..self.seats.len() {
+            for hand in 0..self.seats[seat].hands.len() {
+                let id = HandId { seat, hand };
+                let player = self.hand(id);
+                let outcome = match player.status() {
+                    PlayerStatus::Surrendered => Outcome::Surrender,
+                    PlayerStatus::Busted => Outcome::Lose,
+                    _ if player.is_blackjack() && self.dealer.is_blackjack() => Outcome::Push,
+                    _ if player.is_blackjack() => Outcome::Blackjack,
+                    _ if self.dealer.is_blackjack() => Outcome::Lose,
+                    _ if self.dealer.is_busted() => Outcome::Win,
+                    _ => match player.total().cmp(&dealer_total) {
+                        std::cmp::Ordering::Greater => Outcome::Win,
+                        std::cmp::Ordering::Equal => Outcome::Push,
+                        std::cmp::Ordering::Less => Outcome::Lose,
+                    },
+                };
+                let paid = self.payout(outcome, player.bet);
+                self.outcomes.push((id, outcome));
+                events.push(Event::Settled(id, outcome));
+                if paid > 0 {
+                    self.seats[seat].bankroll += paid;
+                    events.push(Event::Paid(id, paid));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Deck, Suit};
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Cards are drawn in the given order, suits do not matter
+    fn deck(values: &[u8]) -> Deck {
+        Deck::from_cards(values.iter().map(|&v| Card::from((v, 1))))
+    }
+
+    fn hand(seat: usize, hand: usize) -> HandId {
+        HandId { seat, hand }
+    }
+
+    #[test]
+    fn stay_and_dealer_busts() {
+        // player: 10 7, dealer: 6 10 then draws a king
+        let mut game = GameState::new(vec!["Alice"], deck(&[10, 6, 7, 10, 13]));
+        let events = game.deal();
+        assert_eq!(
+            events,
+            vec![
+                Event::Dealt(hand(0, 0), Card::Ten(Suit::Spade)),
+                Event::DealerDealt(Card::Six(Suit::Spade)),
+                Event::Dealt(hand(0, 0), Card::Seven(Suit::Spade)),
+                Event::DealerHoleCard,
+                Event::Turn(hand(0, 0)),
+            ]
+        );
+        let events = game.apply(Action::Stay).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::Stayed(hand(0, 0), 17),
+                Event::DealerRevealed(Card::Ten(Suit::Spade)),
+                Event::DealerHit(Card::King(Suit::Spade)),
+                Event::DealerBusted(26),
+                Event::Settled(hand(0, 0), Outcome::Win),
+            ]
+        );
+        assert_eq!(game.turn(), None);
+        assert_eq!(game.apply(Action::Hit), Err(ActionErr::RoundOver));
+    }
+
+    #[test]
+    fn push_on_equal_totals() {
+        let mut game = GameState::new(vec!["Alice"], deck(&[10, 10, 8, 8]));
+        game.deal();
+        game.apply(Action::Stay).unwrap();
+        assert_eq!(game.outcomes(), &[(hand(0, 0), Outcome::Push)]);
+    }
+
+    #[test]
+    fn hit_and_bust() {
+        let mut game = GameState::new(vec!["Alice", "Bob"], deck(&[10, 9, 5, 6, 9, 8, 10, 2]));
+        game.deal();
+        assert_eq!(game.turn(), Some(hand(0, 0)));
+        let events = game.apply(Action::Hit).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::Dealt(hand(0, 0), Card::Ten(Suit::Spade)),
+                Event::Busted(hand(0, 0), 26),
+                Event::Turn(hand(1, 0)),
+            ]
+        );
+        // Bob stays at 18 and the dealer draws from 13 to 18
+        let mut game = GameState::new(vec!["Alice", "Bob"], deck(&[10, 9, 5, 6, 9, 8, 10, 2, 3]));
+        game.deal();
+        game.apply(Action::Hit).unwrap();
+        let events = game.apply(Action::Stay).unwrap();
+        assert!(events.contains(&Event::DealerStayed(18)));
+        assert_eq!(
+            game.outcomes(),
+            &[(hand(0, 0), Outcome::Lose), (hand(1, 0), Outcome::Push)]
+        );
+    }
+
+    #[test]
+    fn blackjacks() {
+        // Alice has a Black Jack, Bob doesn't and the dealer stays at 20
+        let mut game = GameState::new(vec!["Alice", "Bob"], deck(&[1, 9, 10, 13, 9, 10]));
+        let events = game.deal();
+        assert!(events.contains(&Event::Blackjack(hand(0, 0))));
+        assert_eq!(game.turn(), Some(hand(1, 0)));
+        game.apply(Action::Stay).unwrap();
+        assert_eq!(
+            game.outcomes(),
+            &[
+                (hand(0, 0), Outcome::Blackjack),
+                (hand(1, 0), Outcome::Lose)
+            ]
+        );
+
+        // The dealer has a Black Jack, the round ends right after the deal
+        let mut game = GameState::new(vec!["Alice", "Bob"], deck(&[1, 9, 1, 13, 10, 10]));
+        let events = game.deal();
+        assert_eq!(game.turn(), None);
+        assert!(events.contains(&Event::DealerRevealed(Card::Ten(Suit::Spade))));
+        assert_eq!(
+            game.outcomes(),
+            &[(hand(0, 0), Outcome::Push), (hand(1, 0), Outcome::Lose)]
+        );
+    }
+
+    #[test]
+    fn double_down() {
+        let mut game = GameState::new(vec!["Alice"], deck(&[6, 10, 5, 7, 10]));
+        game.deal();
+        let events = game.apply(Action::Double).unwrap();
+        assert_eq!(
+            &events[..3],
+            &[
+                Event::Doubled(hand(0, 0)),
+                Event::Dealt(hand(0, 0), Card::Ten(Suit::Spade)),
+                Event::Stayed(hand(0, 0), 21),
+            ]
+        );
+        assert!(game.hand(hand(0, 0)).is_doubled());
+        assert_eq!(game.outcomes(), &[(hand(0, 0), Outcome::Win)]);
+    }
+
+    #[test]
+    fn split() {
+        // Alice splits 8s, gets 3 (then hits a 10) on the first and a 5 on the second
+        let mut game = GameState::new(vec!["Alice"], deck(&[8, 10, 8, 7, 3, 5, 10]));
+        game.deal();
+        assert!(game.available_actions().contains(&Action::Split));
+        let events = game.apply(Action::Split).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::Split(hand(0, 0), hand(0, 1)),
+                Event::Dealt(hand(0, 0), Card::Three(Suit::Spade)),
+                Event::Dealt(hand(0, 1), Card::Five(Suit::Spade)),
+            ]
+        );
+        assert_eq!(game.turn(), Some(hand(0, 0)));
+        assert!(!game.available_actions().contains(&Action::Surrender));
+        let events = game.apply(Action::Hit).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::Dealt(hand(0, 0), Card::Ten(Suit::Spade)),
+                Event::Stayed(hand(0, 0), 21),
+                Event::Turn(hand(0, 1)),
+            ]
+        );
+        game.apply(Action::Stay).unwrap();
+        assert_eq!(
+            game.outcomes(),
+            &[(hand(0, 0), Outcome::Win), (hand(0, 1), Outcome::Lose)]
+        );
+    }
+
+    #[test]
+    fn split_aces() {
+        let mut game = GameState::new(vec!["Alice"], deck(&[1, 10, 1, 9, 10, 10]));
+        game.deal();
+        let events = game.apply(Action::Split).unwrap();
+        assert!(events.contains(&Event::Stayed(hand(0, 0), 21)));
+        assert!(events.contains(&Event::Stayed(hand(0, 1), 21)));
+        // 21 after a split is not a Black Jack
+        assert_eq!(
+            game.outcomes(),
+            &[(hand(0, 0), Outcome::Win), (hand(0, 1), Outcome::Win)]
+        );
+    }
+
+    #[test]
+    fn surrender() {
+        let mut game = GameState::new(vec!["Alice"], deck(&[10, 10, 6, 9]));
+        game.deal();
+        let events = game.apply(Action::Surrender).unwrap();
+        assert_eq!(events[0], Event::Surrendered(hand(0, 0)));
+        // The dealer does not need to draw when every hand is done
+        assert!(!events.iter().any(|e| matches!(e, Event::DealerHit(_))));
+        assert_eq!(game.outcomes(), &[(hand(0, 0), Outcome::Surrender)]);
+    }
+
+    #[test]
+    fn actions_not_allowed() {
+        let mut game = GameState::new(vec!["Alice"], deck(&[10, 10, 6, 9, 2, 2]));
+        assert_eq!(game.apply(Action::Hit), Err(ActionErr::RoundOver));
+        game.deal();
+        assert_eq!(
+            game.apply(Action::Split),
+            Err(ActionErr::NotAllowed(Action::Split))
+        );
+        game.apply(Action::Hit).unwrap();
+        assert_eq!(game.available_actions(), vec![Action::Hit, Action::Stay]);
+        assert_eq!(
+            game.apply(Action::Double),
+            Err(ActionErr::NotAllowed(Action::Double))
+        );
+    }
+
+    #[test]
+    fn soft_dealer_total() {
+        // dealer: A 6 is a soft 17 so the dealer stays, then A 6 10 is a hard 17
+        let mut game = GameState::new(vec!["Alice"], deck(&[10, 1, 8, 6]));
+        game.deal();
+        let events = game.apply(Action::Stay).unwrap();
+        assert!(events.contains(&Event::DealerStayed(17)));
+        assert_eq!(game.outcomes(), &[(hand(0, 0), Outcome::Win)]);
+    }
+
+    #[test]
+    fn seeded_games_are_reproducible() {
+        let play = |seed| {
+            let mut deck = Deck::new();
+            deck.shuffle_with(&mut StdRng::seed_from_u64(seed));
+            let mut game = GameState::new(vec!["Alice", "Bob"], Shoe::from(deck));
+            let mut events = game.deal();
+            while game.turn().is_some() {
+                events.extend(game.apply(Action::Stay).unwrap());
+            }
+            events
+        };
+        assert_eq!(play(42), play(42));
+        assert_ne!(play(1), play(2));
+    }
+
+    #[test]
+    fn bets_and_payouts() {
+        // Alice has a Black Jack, Bob pushes at 20 and Carol surrenders
+        let cards = [1, 10, 10, 10, 10, 13, 10, 10, 6];
+        let mut game = GameState::new(vec!["Alice", "Bob", "Carol"], deck(&cards));
+        for seat in 0..3 {
+            game.deposit(seat, 100);
+        }
+        game.place_bet(0, 20).unwrap();
+        game.place_bet(1, 50).unwrap();
+        game.place_bet(1, 30).unwrap();
+        assert_eq!(game.place_bet(2, 101), Err(BetErr::InsufficientFunds));
+        game.place_bet(2, 15).unwrap();
+        assert_eq!(game.bankroll(1), 70);
+
+        game.deal();
+        assert_eq!(game.place_bet(0, 10), Err(BetErr::RoundInProgress));
+        game.apply(Action::Stay).unwrap();
+        let events = game.apply(Action::Surrender).unwrap();
+        assert!(events.contains(&Event::Paid(hand(0, 0), 50)));
+        assert!(events.contains(&Event::Paid(hand(2, 0), 7)));
+        assert_eq!(
+            game.outcomes(),
+            &[
+                (hand(0, 0), Outcome::Blackjack),
+                (hand(1, 0), Outcome::Push),
+                (hand(2, 0), Outcome::Surrender)
+            ]
+        );
+        assert_eq!(game.bankroll(0), 130);
+        assert_eq!(game.bankroll(1), 100);
+        assert_eq!(game.bankroll(2), 92);
+
+        // Bets are only good for a single round
+        game.deal();
+        assert_eq!(game.hand(hand(0, 0)).bet(), 0);
+    }
+
+    #[test]
+    fn doubling_and_splitting_need_funds() {
+        let mut game = GameState::new(vec!["Alice"], deck(&[8, 10, 8, 7, 3, 5, 10]));
+        game.deposit(0, 15);
+        game.place_bet(0, 10).unwrap();
+        game.deal();
+        assert_eq!(
+            game.available_actions(),
+            vec![Action::Hit, Action::Stay, Action::Surrender]
+        );
+        assert_eq!(
+            game.apply(Action::Split),
+            Err(ActionErr::NotAllowed(Action::Split))
+        );
+
+        let mut game = GameState::new(vec!["Alice"], deck(&[8, 10, 8, 7, 3, 5, 10]));
+        game.deposit(0, 20);
+        game.place_bet(0, 10).unwrap();
+        game.deal();
+        game.apply(Action::Split).unwrap();
+        assert_eq!(game.bankroll(0), 0);
+        // The first hand is now 8 3, but there is nothing left to double down with
+        assert!(!game.available_actions().contains(&Action::Double));
+        game.apply(Action::Stay).unwrap();
+        game.apply(Action::Stay).unwrap();
+        assert_eq!(game.hands(0).iter().map(Player::bet).sum::<u64>(), 20);
+    }
+
+    #[test]
+    fn doubled_bet_payout() {
+        let mut game = GameState::new(vec!["Alice"], deck(&[6, 10, 5, 7, 10]));
+        game.deposit(0, 50);
+        game.place_bet(0, 10).unwrap();
+        game.deal();
+        let events = game.apply(Action::Double).unwrap();
+        assert_eq!(game.hand(hand(0, 0)).bet(), 20);
+        assert!(events.contains(&Event::Paid(hand(0, 0), 40)));
+        assert_eq!(game.bankroll(0), 70);
+    }
+
+    #[test]
+    fn abort_round_returns_bets() {
+        // Alice doubles on 6 5, then the round is abandoned during Bob's turn
+        let mut game = GameState::new(vec!["Alice", "Bob"], deck(&[6, 10, 10, 5, 7, 7, 10]));
+        game.deposit(0, 50);
+        game.deposit(1, 50);
+        assert!(game.abort_round().is_empty());
+        game.place_bet(0, 10).unwrap();
+        game.place_bet(1, 5).unwrap();
+        game.deal();
+        game.apply(Action::Double).unwrap();
+        assert_eq!(game.turn(), Some(hand(1, 0)));
+
+        let events = game.abort_round();
+        assert_eq!(
+            events,
+            vec![Event::Paid(hand(0, 0), 20), Event::Paid(hand(1, 0), 5)]
+        );
+        assert_eq!((game.bankroll(0), game.bankroll(1)), (50, 50));
+        assert_eq!(game.turn(), None);
+        assert!(game.outcomes().is_empty());
+        assert_eq!(game.place_bet(0, 10), Ok(()));
+    }
+
+    #[test]
+    fn dealer_soft_17() {
+        // Dealer has A 6, drawing a 3 gets to 20 while the player stays at 18
+        let cards = [10, 1, 8, 6, 3];
+        let mut game = GameState::new(vec!["Alice"], deck(&cards));
+        game.deal();
+        game.apply(Action::Stay).unwrap();
+        assert_eq!(game.outcomes(), &[(hand(0, 0), Outcome::Win)]);
+
+        let rules = Rules {
+            dealer_hits_soft_17: true,
+            ..Rules::default()
+        };
+        let mut game = GameState::with_rules(vec!["Alice"], deck(&cards), rules);
+        game.deal();
+        let events = game.apply(Action::Stay).unwrap();
+        assert!(events.contains(&Event::DealerStayed(20)));
+        assert_eq!(game.outcomes(), &[(hand(0, 0), Outcome::Lose)]);
+    }
+
+    #[test]
+    fn blackjack_payout_rule() {
+        let rules = Rules {
+            blackjack_payout: (6, 5),
+            ..Rules::default()
+        };
+        let mut game = GameState::with_rules(vec!["Alice"], deck(&[1, 10, 10, 9]), rules);
+        game.deposit(0, 25);
+        game.place_bet(0, 25).unwrap();
+        let events = game.deal();
+        assert!(events.contains(&Event::Paid(hand(0, 0), 55)));
+    }
+
+    #[test]
+    fn shoe_is_reshuffled_at_cut_card() {
+        let shoe = Shoe::seeded(2, 7).with_penetration(0.5);
+        let mut game = GameState::new(vec!["Alice", "Bob"], shoe);
+        let mut rounds_since_shuffle = 0;
+        let mut shuffles = 0;
+        for _ in 0..60 {
+            let events = game.deal();
+            if events.contains(&Event::Shuffled) {
+                shuffles += 1;
+                // 52 cards are dealt before the cut card, and a round uses at most 52 cards
+                assert!(rounds_since_shuffle > 1);
+                rounds_since_shuffle = 0;
+            }
+            rounds_since_shuffle += 1;
+            while game.turn().is_some() {
+                game.apply(Action::Stay).unwrap();
+            }
+        }
+        assert!(shuffles > 3);
+    }
+}
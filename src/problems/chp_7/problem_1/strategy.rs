@@ -0,0 +1,380 @@
+use super::{Action, Card, GameState, Outcome, Player, Rules, Shoe};
+
+/// Decides what to do with a hand, `available` is never empty and the returned action must be
+/// one of them. Any `FnMut(&Player, Card, &[Action]) -> Action` closure implements it.
+pub trait Strategy {
+    fn decide(&mut self, hand: &Player, up_card: Card, available: &[Action]) -> Action;
+}
+
+impl<F: FnMut(&Player, Card, &[Action]) -> Action> Strategy for F {
+    fn decide(&mut self, hand: &Player, up_card: Card, available: &[Action]) -> Action {
+        self(hand, up_card, available)
+    }
+}
+
+/// Strategy following [`basic_strategy`] for the given [Rules]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BasicStrategy {
+    rules: Rules,
+}
+
+impl BasicStrategy {
+    pub fn new(rules: Rules) -> Self {
+        Self { rules }
+    }
+}
+
+impl Strategy for BasicStrategy {
+    fn decide(&mut self, hand: &Player, up_card: Card, available: &[Action]) -> Action {
+        basic_strategy(hand, up_card, available, &self.rules)
+    }
+}
+
+/// # Basic strategy
+///
+/// The usual multi-deck chart with doubling after splits and late surrender, for a dealer
+/// standing on soft 17 or hitting it depending on `rules`. The dealer hitting soft 17 makes
+/// doubling soft 18 against a 2, soft 19 against a 6 and 11 against an ace worth it, as well as
+/// surrendering 15 and 17 against an ace. When the chart says to double, split or surrender but
+/// the action is not available, the best remaining action is returned instead (hitting, or
+/// staying on a soft 18).
+pub fn basic_strategy(hand: &Player, up_card: Card, available: &[Action], rules: &Rules) -> Action {
+    let can = |action| available.contains(&action);
+    let hits_soft_17 = rules.dealer_hits_soft_17;
+    // Ace counts as 11 so the dealer's card goes from 2 to 11
+    let up = up_card.val(true);
+    let total = hand.total();
+
+    let is_pair = hand.hand.len() == 2 && hand.hand[0].val(false) == hand.hand[1].val(false);
+    if is_pair && can(Action::Split) {
+        let split = match hand.hand[0].val(true) {
+            11 | 8 => true,
+            9 => !matches!(up, 7 | 10 | 11),
+            7 | 3 | 2 => up <= 7,
+            6 => up <= 6,
+            4 => matches!(up, 5 | 6),
+            _ => false,
+        };
+        if split {
+            return Action::Split;
+        }
+    }
+
+    if can(Action::Surrender) && !hand.is_soft() {
+        let surrender = match total {
+            17 => hits_soft_17 && up == 11,
+            16 => up >= 9,
+            15 => up == 10 || hits_soft_17 && up == 11,
+            _ => false,
+        };
+        if surrender {
+            return Action::Surrender;
+        }
+    }
+
+    let double_or = |otherwise| {
+        if can(Action::Double) {
+            Action::Double
+        } else {
+            otherwise
+        }
+    };
+
+    if hand.is_soft() {
+        return match total {
+            20..=21 => Action::Stay,
+            19 if hits_soft_17 && up == 6 => double_or(Action::Stay),
+            19 => Action::Stay,
+            18 if (3..=6).contains(&up) || hits_soft_17 && up == 2 => double_or(Action::Stay),
+            18 if up <= 8 => Action::Stay,
+            17 if (3..=6).contains(&up) => double_or(Action::Hit),
+            15..=16 if (4..=6).contains(&up) => double_or(Action::Hit),
+            13..=14 if (5..=6).contains(&up) => double_or(Action::Hit),
+            _ => Action::Hit,
+        };
+    }
+
+    match total {
+        17..=21 => Action::Stay,
+        13..=16 if up <= 6 => Action::Stay,
+        12 if (4..=6).contains(&up) => Action::Stay,
+        11 if up <= 10 || hits_soft_17 => double_or(Action::Hit),
+        10 if up <= 9 => double_or(Action::Hit),
+        9 if (3..=6).contains(&up) => double_or(Action::Hit),
+        _ => Action::Hit,
+    }
+}
+
+/// Results of a [Simulator] run, every hand resulting from a split is counted on its own.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct SimulationReport {
+    pub rounds: u64,
+    pub hands: u64,
+    /// Including Black Jacks
+    pub wins: u64,
+    pub blackjacks: u64,
+    pub pushes: u64,
+    /// Including surrendered hands
+    pub losses: u64,
+    /// Total amount bet, including doubles and splits
+    pub wagered: u64,
+    /// Amount won by the player, negative when the house wins
+    pub net: i64,
+}
+
+impl SimulationReport {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.hands as f64
+    }
+
+    pub fn push_rate(&self) -> f64 {
+        self.pushes as f64 / self.hands as f64
+    }
+
+    pub fn loss_rate(&self) -> f64 {
+        self.losses as f64 / self.hands as f64
+    }
+
+    /// Expected loss of the player per initial bet, ie. 0.005 means the house keeps half a
+    /// percent of every bet on average.
+    pub fn house_edge(&self) -> f64 {
+        -self.net as f64 / self.rounds as f64 / Simulator::BET as f64
+    }
+}
+
+/// # Monte Carlo simulation
+///
+/// Plays rounds of a single seat against the dealer with a seeded [Shoe], so a given seed always
+/// plays the exact same rounds and different strategies can be compared on the same cards.
+#[derive(Debug, Clone, Copy)]
+pub struct Simulator {
+    seed: u64,
+    decks: usize,
+    penetration: f64,
+    rules: Rules,
+}
+
+impl Simulator {
+    /// Initial bet of every round, even so that surrendering does not lose a fraction of a chip
+    const BET: u64 = 2;
+
+    /// Six decks dealt up to 75% with the default [Rules]
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            decks: 6,
+            penetration: 0.75,
+            rules: Rules::default(),
+        }
+    }
+
+    pub fn with_decks(self, decks: usize) -> Self {
+        Self { decks, ..self }
+    }
+
+    pub fn with_penetration(self, penetration: f64) -> Self {
+        Self {
+            penetration,
+            ..self
+        }
+    }
+
+    pub fn with_rules(self, rules: Rules) -> Self {
+        Self { rules, ..self }
+    }
+
+    pub fn run<S: Strategy + ?Sized>(&self, strategy: &mut S, rounds: u64) -> SimulationReport {
+        let shoe = Shoe::seeded(self.decks, self.seed).with_penetration(self.penetration);
+        let mut game = GameState::with_rules(vec!["Player"], shoe, self.rules);
+        let mut report = SimulationReport {
+            rounds,
+            ..SimulationReport::default()
+        };
+        // Never runs out, only `net` tracks the result
+        game.deposit(0, 1 << 40);
+
+        for _ in 0..rounds {
+            let before = game.bankroll(0);
+            game.place_bet(0, Self::BET).unwrap();
+            game.deal();
+
+            while let Some(id) = game.turn() {
+                let available = game.available_actions();
+                let up_card = game.dealer_up_card().unwrap();
+                let action = strategy.decide(game.hand(id), up_card, &available);
+                if game.apply(action).is_err() {
+                    panic!(
+                        "{:?} is not one of the available actions {:?}",
+                        action, available
+                    );
+                }
+            }
+
+            report.wagered += game.hands(0).iter().map(Player::bet).sum::<u64>();
+            report.net += game.bankroll(0) as i64 - before as i64;
+            for (_, outcome) in game.outcomes() {
+                report.hands += 1;
+                match outcome {
+                    Outcome::Blackjack => {
+                        report.blackjacks += 1;
+                        report.wins += 1;
+                    }
+                    Outcome::Win => report.wins += 1,
+                    Outcome::Push => report.pushes += 1,
+                    Outcome::Lose | Outcome::Surrender => report.losses += 1,
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(values: &[u8]) -> Player {
+        let mut player = Player::new("Alice");
+        for &v in values {
+            player.hit(Card::from((v, 1)));
+        }
+        player
+    }
+
+    fn advise(values: &[u8], up: u8) -> Action {
+        advise_with(values, up, &Rules::default())
+    }
+
+    fn advise_with(values: &[u8], up: u8, rules: &Rules) -> Action {
+        let all = [
+            Action::Hit,
+            Action::Stay,
+            Action::Double,
+            Action::Split,
+            Action::Surrender,
+        ];
+        basic_strategy(&hand(values), Card::from((up, 1)), &all, rules)
+    }
+
+    #[test]
+    fn basic_strategy_chart() {
+        assert_eq!(advise(&[10, 7], 1), Action::Stay);
+        assert_eq!(advise(&[10, 6], 6), Action::Stay);
+        assert_eq!(advise(&[10, 6], 7), Action::Hit);
+        assert_eq!(advise(&[10, 6], 10), Action::Surrender);
+        assert_eq!(advise(&[10, 2], 3), Action::Hit);
+        assert_eq!(advise(&[10, 2], 4), Action::Stay);
+        assert_eq!(advise(&[6, 5], 10), Action::Double);
+        assert_eq!(advise(&[6, 5], 1), Action::Hit);
+        assert_eq!(advise(&[5, 4], 2), Action::Hit);
+        assert_eq!(advise(&[5, 4], 3), Action::Double);
+
+        assert_eq!(advise(&[1, 7], 4), Action::Double);
+        assert_eq!(advise(&[1, 7], 8), Action::Stay);
+        assert_eq!(advise(&[1, 7], 9), Action::Hit);
+        assert_eq!(advise(&[1, 2], 5), Action::Double);
+        assert_eq!(advise(&[1, 2], 4), Action::Hit);
+
+        assert_eq!(advise(&[1, 1], 10), Action::Split);
+        assert_eq!(advise(&[8, 8], 1), Action::Split);
+        assert_eq!(advise(&[9, 9], 7), Action::Stay);
+        assert_eq!(advise(&[13, 12], 6), Action::Stay);
+        assert_eq!(advise(&[5, 5], 6), Action::Double);
+        assert_eq!(advise(&[4, 4], 5), Action::Split);
+        assert_eq!(advise(&[4, 4], 4), Action::Hit);
+    }
+
+    #[test]
+    fn dealer_hitting_soft_17() {
+        let h17 = Rules {
+            dealer_hits_soft_17: true,
+            ..Rules::default()
+        };
+        // A 8 against a 6
+        assert_eq!(advise(&[1, 8], 6), Action::Stay);
+        assert_eq!(advise_with(&[1, 8], 6, &h17), Action::Double);
+        // A 7 against a 2
+        assert_eq!(advise(&[1, 7], 2), Action::Stay);
+        assert_eq!(advise_with(&[1, 7], 2, &h17), Action::Double);
+        assert_eq!(advise(&[1, 7], 3), Action::Double);
+        assert_eq!(advise_with(&[1, 7], 3, &h17), Action::Double);
+
+        assert_eq!(advise_with(&[6, 5], 1, &h17), Action::Double);
+        assert_eq!(advise(&[10, 5], 1), Action::Hit);
+        assert_eq!(advise_with(&[10, 5], 1, &h17), Action::Surrender);
+        assert_eq!(advise(&[10, 7], 1), Action::Stay);
+        assert_eq!(advise_with(&[10, 7], 1, &h17), Action::Surrender);
+    }
+
+    #[test]
+    fn basic_strategy_fallbacks() {
+        let hit_or_stay = [Action::Hit, Action::Stay];
+        let rules = Rules::default();
+        let up = |v| Card::from((v, 1));
+        assert_eq!(
+            basic_strategy(&hand(&[1, 7]), up(4), &hit_or_stay, &rules),
+            Action::Stay
+        );
+        assert_eq!(
+            basic_strategy(&hand(&[6, 5]), up(9), &hit_or_stay, &rules),
+            Action::Hit
+        );
+        assert_eq!(
+            basic_strategy(&hand(&[10, 6]), up(10), &hit_or_stay, &rules),
+            Action::Hit
+        );
+        // 8 8 can't be split so it is played as a hard 16
+        assert_eq!(
+            basic_strategy(&hand(&[8, 8]), up(6), &hit_or_stay, &rules),
+            Action::Stay
+        );
+        // Doubling is only offered on the first two cards, so a three card 11 is hit
+        assert_eq!(
+            basic_strategy(&hand(&[3, 3, 5]), up(6), &hit_or_stay, &rules),
+            Action::Hit
+        );
+    }
+
+    #[test]
+    fn simulation_is_reproducible() {
+        let simulator = Simulator::new(42).with_decks(2);
+        let first = simulator.run(&mut BasicStrategy::default(), 2_000);
+        let second = simulator.run(&mut BasicStrategy::default(), 2_000);
+        assert_eq!(first, second);
+        assert_eq!(first.rounds, 2_000);
+        assert!(first.hands >= first.rounds);
+        assert_eq!(first.wins + first.pushes + first.losses, first.hands);
+        let rates = first.win_rate() + first.push_rate() + first.loss_rate();
+        assert!((rates - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn basic_strategy_beats_naive_strategies() {
+        let simulator = Simulator::new(7);
+        let rounds = 20_000;
+        let basic = simulator.run(&mut BasicStrategy::default(), rounds);
+        // Never risk busting
+        let mut never_bust = |hand: &Player, _: Card, _: &[Action]| {
+            if hand.total() >= 12 {
+                Action::Stay
+            } else {
+                Action::Hit
+            }
+        };
+        let never_bust = simulator.run(&mut never_bust, rounds);
+        // Play like the dealer, which gives the house an edge of about 5%
+        let mut mimic_dealer = |hand: &Player, _: Card, _: &[Action]| {
+            if hand.total() >= 17 {
+                Action::Stay
+            } else {
+                Action::Hit
+            }
+        };
+        let mimic_dealer = simulator.run(&mut mimic_dealer, rounds);
+
+        assert!(basic.house_edge().abs() < 0.03, "{:?}", basic);
+        assert!(basic.house_edge() < mimic_dealer.house_edge());
+        assert!(basic.house_edge() < never_bust.house_edge());
+        assert_eq!(never_bust.hands, never_bust.rounds);
+    }
+}
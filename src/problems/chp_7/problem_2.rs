@@ -1,40 +1,107 @@
-/// After looking at the solution I realized I forgot to include the "escalate" ability.
-/// I might revisit this in the future and also add a "call_queue", but to be honest I the OOP
-/// section of the book is not really what I was looking to practice.
+/// After looking at the solution I realized I forgot to include the "escalate" ability, so I came
+/// back to add it along with a call queue.
+///
+/// Calls that cannot be answered right away wait in the queue, ordered by [Priority] and then by
+/// arrival. Whenever an employee becomes available (a call ends or is escalated), the waiting call
+/// with the highest priority that they can handle is answered. Employees of the same role are
+/// always picked in a round robin fashion, the one who has been available the longest first.
 ///
 /// Additionally OOP interviews don't ususally require full implementations, but rather a sketch of
 /// the overall architecture, so this is of low priority for me
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+
+mod simulation;
+pub use simulation::*;
 
 pub struct CallCenter {
     respondents: VecDeque<Respondent>,
     managers: VecDeque<Manager>,
     directors: VecDeque<Director>,
+    queue: Vec<QueuedCall>,
+    history: HashMap<String, Vec<CallRecord>>,
+    next_id: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CallId(u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+pub enum Employee {
+    Respondent(Respondent),
+    Manager(Manager),
+    Director(Director),
+}
+
+/// A call being handled by an employee, hand it back with [`CallCenter::end_call`] or
+/// [`CallCenter::escalate`].
+pub struct Call {
+    id: CallId,
+    priority: Priority,
+    employee: Employee,
 }
 
-pub enum Call {
-    RespondentCall(Respondent),
-    ManagerCall(Manager),
-    DirectorCall(Director),
+/// Result of a call reaching the call center
+pub enum Dispatch {
+    Answered(Call),
+    /// No one who can handle the call is available, it will be answered by
+    /// [`end_call`](CallCenter::end_call) or [`escalate`](CallCenter::escalate) once someone is.
+    Queued(CallId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ended,
+    Escalated,
+}
+
+/// Entry of an employee's call history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallRecord {
+    pub id: CallId,
+    pub priority: Priority,
+    pub resolution: Resolution,
+}
+
+struct QueuedCall {
+    id: CallId,
+    priority: Priority,
+    /// Lowest role allowed to answer the call
+    role: Role,
 }
 
 /// Added a couple helper methods to help with testing
 impl Call {
     pub fn name(&self) -> &str {
-        match self {
-            Call::RespondentCall(employee) => employee.name(),
-            Call::ManagerCall(employee) => employee.name(),
-            Call::DirectorCall(employee) => employee.name(),
+        match &self.employee {
+            Employee::Respondent(employee) => employee.name(),
+            Employee::Manager(employee) => employee.name(),
+            Employee::Director(employee) => employee.name(),
         }
     }
 
     pub fn role(&self) -> Role {
-        match self {
-            Call::RespondentCall(employee) => employee.role(),
-            Call::ManagerCall(employee) => employee.role(),
-            Call::DirectorCall(employee) => employee.role(),
+        match &self.employee {
+            Employee::Respondent(employee) => employee.role(),
+            Employee::Manager(employee) => employee.role(),
+            Employee::Director(employee) => employee.role(),
         }
     }
+
+    pub fn id(&self) -> CallId {
+        self.id
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
 }
 
 impl CallCenter {
@@ -43,6 +110,9 @@ impl CallCenter {
             respondents: VecDeque::new(),
             managers: VecDeque::new(),
             directors: VecDeque::new(),
+            queue: Vec::new(),
+            history: HashMap::new(),
+            next_id: 0,
         }
     }
 
@@ -55,45 +125,188 @@ impl CallCenter {
             respondents: Respondent::names_to_queue(respondents),
             managers: Manager::names_to_queue(managers),
             directors: Director::names_to_queue(directors),
+            ..Self::new()
         }
     }
 
+    fn new_id(&mut self) -> CallId {
+        self.next_id += 1;
+        CallId(self.next_id)
+    }
+
+    /// Takes the available employee with the lowest role able to handle a call for `role`
+    fn take_employee(&mut self, role: Role) -> Option<Employee> {
+        if role == Role::Respondent {
+            if let Some(respondent) = self.respondents.pop_front() {
+                return Some(Employee::Respondent(respondent));
+            }
+        }
+        if role <= Role::Manager {
+            if let Some(manager) = self.managers.pop_front() {
+                return Some(Employee::Manager(manager));
+            }
+        }
+        self.directors.pop_front().map(Employee::Director)
+    }
+
+    /// Answers the call right away, without queuing it if no one is available.
     pub fn dispatch_call(&mut self, role: Role) -> Result<Call, String> {
-        if let (false, true) = (self.respondents.is_empty(), role == Role::Respondent) {
-            return Ok(Call::RespondentCall(self.respondents.pop_front().unwrap()));
+        let employee = self
+            .take_employee(role)
+            .ok_or_else(|| String::from("No one is available"))?;
+        Ok(Call {
+            id: self.new_id(),
+            priority: Priority::default(),
+            employee,
+        })
+    }
+
+    /// Answers the call if someone is available, otherwise it waits in the queue. Calls already
+    /// waiting are answered first, unless this one has a higher priority.
+    pub fn receive_call(&mut self, role: Role, priority: Priority) -> Dispatch {
+        let id = self.new_id();
+        self.route(QueuedCall { id, priority, role })
+    }
+
+    fn route(&mut self, call: QueuedCall) -> Dispatch {
+        let ahead = self
+            .queue
+            .iter()
+            .any(|queued| queued.priority >= call.priority && queued.role <= call.role);
+        if !ahead {
+            if let Some(employee) = self.take_employee(call.role) {
+                return Dispatch::Answered(Call {
+                    id: call.id,
+                    priority: call.priority,
+                    employee,
+                });
+            }
         }
-        if let (false, true) = (self.managers.is_empty(), role <= Role::Manager) {
-            return Ok(Call::ManagerCall(self.managers.pop_front().unwrap()));
+        let id = call.id;
+        self.queue.push(call);
+        Dispatch::Queued(id)
+    }
+
+    fn record(&mut self, call: &Call, resolution: Resolution) {
+        self.history
+            .entry(call.name().to_string())
+            .or_default()
+            .push(CallRecord {
+                id: call.id,
+                priority: call.priority,
+                resolution,
+            });
+    }
+
+    /// Puts the employee back to the end of the line of their role, then answers the next waiting
+    /// call they (or anyone else available) can handle.
+    fn release(&mut self, employee: Employee) -> Option<Call> {
+        match employee {
+            Employee::Respondent(respondent) => self.respondents.push_back(respondent),
+            Employee::Manager(manager) => self.managers.push_back(manager),
+            Employee::Director(director) => self.directors.push_back(director),
         }
-        if let (false, true) = (self.directors.is_empty(), role <= Role::Director) {
-            return Ok(Call::DirectorCall(self.directors.pop_front().unwrap()));
+        self.answer_next()
+    }
+
+    fn answer_next(&mut self) -> Option<Call> {
+        let max_role = if !self.directors.is_empty() {
+            Role::Director
+        } else if !self.managers.is_empty() {
+            Role::Manager
+        } else if !self.respondents.is_empty() {
+            Role::Respondent
+        } else {
+            return None;
+        };
+        // Queued calls are in arrival order, so the first one of the highest priority wins
+        let mut next: Option<usize> = None;
+        for (idx, queued) in self.queue.iter().enumerate() {
+            let better = next.is_none_or(|best| queued.priority > self.queue[best].priority);
+            if queued.role <= max_role && better {
+                next = Some(idx);
+            }
         }
-        Err(String::from("No one is available"))
+        let queued = self.queue.remove(next?);
+        let employee = self.take_employee(queued.role).unwrap();
+        Some(Call {
+            id: queued.id,
+            priority: queued.priority,
+            employee,
+        })
     }
 
-    pub fn end_call(&mut self, call: Call) {
-        match call {
-            Call::RespondentCall(respondent) => self.respondents.push_back(respondent),
-            Call::ManagerCall(manager) => self.managers.push_back(manager),
-            Call::DirectorCall(director) => self.directors.push_back(director),
+    /// Ends the call, if calls are waiting the employee (or someone else who became available)
+    /// answers the next one which is returned.
+    pub fn end_call(&mut self, call: Call) -> Option<Call> {
+        self.record(&call, Resolution::Ended);
+        self.release(call.employee)
+    }
+
+    /// Hands the call to the next role up, keeping its id and priority. The call is queued ahead
+    /// of every call of the same priority if no one is available to take it. The employee who
+    /// escalated is released, and the next waiting call they answer (if any) is returned as well.
+    ///
+    /// Directors cannot escalate, their calls are given back as the error.
+    pub fn escalate(&mut self, call: Call) -> Result<(Dispatch, Option<Call>), Call> {
+        let role = match call.role() {
+            Role::Respondent => Role::Manager,
+            Role::Manager => Role::Director,
+            Role::Director => return Err(call),
+        };
+        self.record(&call, Resolution::Escalated);
+        let dispatch = match self.take_employee(role) {
+            Some(employee) => Dispatch::Answered(Call {
+                id: call.id,
+                priority: call.priority,
+                employee,
+            }),
+            None => {
+                // Waiting calls are picked by priority then position, so the front of the queue
+                // puts it ahead of the calls of the same priority only
+                let escalated = QueuedCall {
+                    id: call.id,
+                    priority: call.priority,
+                    role,
+                };
+                self.queue.insert(0, escalated);
+                Dispatch::Queued(call.id)
+            }
+        };
+        Ok((dispatch, self.release(call.employee)))
+    }
+
+    /// Removes a waiting call from the queue, returns `false` if it is not waiting anymore.
+    pub fn abandon(&mut self, id: CallId) -> bool {
+        match self.queue.iter().position(|queued| queued.id == id) {
+            Some(pos) => {
+                self.queue.remove(pos);
+                true
+            }
+            None => false,
         }
     }
+
+    /// Number of calls waiting to be answered
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Calls handled by the employee called `name`, oldest first
+    pub fn history(&self, name: &str) -> &[CallRecord] {
+        self.history.get(name).map_or(&[], Vec::as_slice)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 #[repr(u8)]
 pub enum Role {
+    #[default]
     Respondent = 0,
     Manager = 1,
     Director = 2,
 }
 
-impl Default for Role {
-    fn default() -> Self {
-        Self::Respondent
-    }
-}
-
 pub struct Respondent {
     name: String,
     role: Role,
@@ -221,4 +434,148 @@ mod tests {
         assert_eq!(fourth_call.name(), "Patrick");
         assert_eq!(fourth_call.role(), Role::Director);
     }
+
+    fn answered(dispatch: Dispatch) -> Call {
+        match dispatch {
+            Dispatch::Answered(call) => call,
+            Dispatch::Queued(id) => panic!("{:?} was queued", id),
+        }
+    }
+
+    fn queued(dispatch: Dispatch) -> CallId {
+        match dispatch {
+            Dispatch::Queued(id) => id,
+            Dispatch::Answered(call) => panic!("{:?} was answered by {}", call.id(), call.name()),
+        }
+    }
+
+    #[test]
+    fn call_queue_priority() {
+        let mut call_center = CallCenter::from_iters(vec!["James"], vec![], vec![]);
+
+        let first = answered(call_center.receive_call(Role::Respondent, Priority::Normal));
+        let low = queued(call_center.receive_call(Role::Respondent, Priority::Low));
+        let normal = queued(call_center.receive_call(Role::Respondent, Priority::Normal));
+        let urgent = queued(call_center.receive_call(Role::Respondent, Priority::Urgent));
+        assert_eq!(call_center.queue_len(), 3);
+        assert!(call_center.dispatch_call(Role::Respondent).is_err());
+
+        let second = call_center.end_call(first).unwrap();
+        assert_eq!(second.id(), urgent);
+        assert_eq!(second.name(), "James");
+        let third = call_center.end_call(second).unwrap();
+        assert_eq!(third.id(), normal);
+        assert!(call_center.abandon(low));
+        assert!(!call_center.abandon(low));
+        assert!(call_center.end_call(third).is_none());
+        assert_eq!(call_center.queue_len(), 0);
+    }
+
+    #[test]
+    fn call_queue_respects_roles() {
+        let mut call_center = CallCenter::from_iters(vec!["James", "Anna"], vec!["George"], vec![]);
+
+        let manager_call = answered(call_center.receive_call(Role::Manager, Priority::Normal));
+        let waiting = queued(call_center.receive_call(Role::Manager, Priority::Urgent));
+        // A respondent is still available for lower role calls
+        let respondent_call = answered(call_center.receive_call(Role::Respondent, Priority::Low));
+        assert_eq!(respondent_call.name(), "James");
+
+        // James can't take the manager call, so it keeps waiting
+        assert!(call_center.end_call(respondent_call).is_none());
+        let next = call_center.end_call(manager_call).unwrap();
+        assert_eq!(next.id(), waiting);
+        assert_eq!(next.name(), "George");
+    }
+
+    #[test]
+    fn round_robin_assignment() {
+        let mut call_center = CallCenter::from_iters(vec!["James", "Anna", "Tom"], vec![], vec![]);
+        let calls: Vec<_> = (0..3)
+            .map(|_| call_center.dispatch_call(Role::Respondent).unwrap())
+            .collect();
+        for call in calls {
+            call_center.end_call(call);
+        }
+        // Everyone took one call, James has been available the longest
+        let names: Vec<_> = (0..3)
+            .map(|_| call_center.dispatch_call(Role::Respondent).unwrap())
+            .map(|call| call.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["James", "Anna", "Tom"]);
+    }
+
+    #[test]
+    fn escalation() {
+        let mut call_center = CallCenter::from_iters(["James"], ["George"], ["Patrick"]);
+
+        let call = answered(call_center.receive_call(Role::Respondent, Priority::High));
+        let id = call.id();
+        let (dispatch, picked_up) = call_center.escalate(call).ok().unwrap();
+        assert!(picked_up.is_none());
+        let call = answered(dispatch);
+        assert_eq!((call.id(), call.name()), (id, "George"));
+
+        let (dispatch, _) = call_center.escalate(call).ok().unwrap();
+        let call = answered(dispatch);
+        assert_eq!(call.name(), "Patrick");
+        assert_eq!(call.priority(), Priority::High);
+
+        let call = call_center.escalate(call).err().unwrap();
+        assert_eq!(call.role(), Role::Director);
+        call_center.end_call(call);
+
+        let escalated = CallRecord {
+            id,
+            priority: Priority::High,
+            resolution: Resolution::Escalated,
+        };
+        assert_eq!(call_center.history("James"), &[escalated]);
+        assert_eq!(call_center.history("George"), &[escalated]);
+        assert_eq!(
+            call_center.history("Patrick")[0].resolution,
+            Resolution::Ended
+        );
+        assert!(call_center.history("Nobody").is_empty());
+    }
+
+    #[test]
+    fn escalation_is_queued() {
+        let mut call_center = CallCenter::from_iters(vec!["James", "Anna"], vec!["George"], vec![]);
+
+        let manager_call = call_center.dispatch_call(Role::Manager).unwrap();
+        let first = queued(call_center.receive_call(Role::Manager, Priority::High));
+        let call = answered(call_center.receive_call(Role::Respondent, Priority::High));
+        let _anna_call = answered(call_center.receive_call(Role::Respondent, Priority::Low));
+        let waiting = queued(call_center.receive_call(Role::Respondent, Priority::Normal));
+
+        let (dispatch, picked_up) = call_center.escalate(call).ok().unwrap();
+        let escalated = queued(dispatch);
+        // James is free again and answers the waiting call
+        let james_call = picked_up.unwrap();
+        assert_eq!((james_call.id(), james_call.name()), (waiting, "James"));
+
+        // The escalated call waited less but goes ahead of calls with the same priority
+        let next = call_center.end_call(manager_call).unwrap();
+        assert_eq!(next.id(), escalated);
+        let next = call_center.end_call(next).unwrap();
+        assert_eq!(next.id(), first);
+
+        call_center.end_call(james_call);
+        assert_eq!(
+            call_center.history("James"),
+            &[
+                CallRecord {
+                    id: escalated,
+                    priority: Priority::High,
+                    resolution: Resolution::Escalated
+                },
+                CallRecord {
+                    id: waiting,
+                    priority: Priority::Normal,
+                    resolution: Resolution::Ended
+                }
+            ]
+        );
+    }
 }
@@ -0,0 +1,478 @@
+use super::{Call, CallCenter, CallId, Dispatch, Priority, Role};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+/// Parameters of a [`simulate`] run, times are in seconds and every duration is drawn from an
+/// exponential distribution with the given mean.
+///
+/// Arrays indexed by role go from [Role::Respondent] to [Role::Director], and
+/// `priority_weights` from [Priority::Low] to [Priority::Urgent].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationConfig {
+    pub seed: u64,
+    /// Number of employees of each role
+    pub staff: [usize; 3],
+    /// Number of incoming calls to simulate
+    pub calls: usize,
+    pub mean_arrival_interval: f64,
+    pub mean_handling_time: [f64; 3],
+    /// Probability that a call handled by each role has to be escalated once handled, directors
+    /// cannot escalate so the last value is ignored.
+    pub escalation_probability: [f64; 3],
+    /// Mean time a caller is willing to wait in the queue before hanging up, `None` if callers
+    /// never hang up.
+    pub mean_patience: Option<f64>,
+    /// Relative frequency of each priority for incoming calls
+    pub priority_weights: [u32; 4],
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            staff: [5, 2, 1],
+            calls: 1000,
+            mean_arrival_interval: 60.0,
+            mean_handling_time: [240.0, 360.0, 480.0],
+            escalation_probability: [0.1, 0.05, 0.0],
+            mean_patience: Some(300.0),
+            priority_weights: [0, 1, 0, 0],
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Checks that every call can be answered and that the escalation probabilities of
+    /// respondents and managers are between 0 and 1.
+    pub fn validate(&self) -> Result<(), SimulationErr> {
+        if self.staff.iter().all(|&count| count == 0) {
+            return Err(SimulationErr::NoStaff);
+        }
+        let roles = [Role::Respondent, Role::Manager];
+        for (&role, &probability) in roles.iter().zip(self.escalation_probability.iter()) {
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(SimulationErr::InvalidProbability { role, probability });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulationErr {
+    /// No employee to answer the calls
+    NoStaff,
+    InvalidProbability {
+        role: Role,
+        probability: f64,
+    },
+}
+
+impl fmt::Display for SimulationErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoStaff => write!(f, "no employee to answer the calls"),
+            Self::InvalidProbability { role, probability } => write!(
+                f,
+                "escalation probability {} of {:?} is not between 0 and 1",
+                probability, role
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SimulationReport {
+    pub calls: usize,
+    pub completed: usize,
+    pub abandoned: usize,
+    pub escalations: usize,
+    /// Time from the first call until the last one was completed or abandoned
+    pub duration: u64,
+    /// Time spent in the queue by each completed call (including after escalations), sorted
+    waits: Vec<u64>,
+    staff: [usize; 3],
+    busy: [u64; 3],
+}
+
+impl SimulationReport {
+    /// Wait time under which `percentile` percent of the completed calls waited, using the
+    /// nearest rank. `None` if no call was completed.
+    pub fn wait_percentile(&self, percentile: f64) -> Option<u64> {
+        if self.waits.is_empty() {
+            return None;
+        }
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * self.waits.len() as f64).ceil();
+        Some(self.waits[(rank as usize).max(1) - 1])
+    }
+
+    pub fn mean_wait(&self) -> f64 {
+        self.waits.iter().sum::<u64>() as f64 / self.waits.len().max(1) as f64
+    }
+
+    pub fn abandonment_rate(&self) -> f64 {
+        self.abandoned as f64 / self.calls.max(1) as f64
+    }
+
+    /// Fraction of the time the employees of `role` spent on calls
+    pub fn utilisation(&self, role: Role) -> f64 {
+        let available = self.staff[role as usize] as u64 * self.duration;
+        if available == 0 {
+            return 0.0;
+        }
+        self.busy[role as usize] as f64 / available as f64
+    }
+}
+
+/// Events happening at the same time are processed in the order they were scheduled, so their own
+/// ordering is never used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SimEvent {
+    Arrival,
+    Finish(CallId),
+    /// The caller hangs up if the call has been waiting since the given time
+    Abandon(CallId, u64),
+}
+
+struct ActiveCall {
+    call: Call,
+    started: u64,
+    escalate: bool,
+}
+
+#[derive(Default)]
+struct CallState {
+    queued_since: Option<u64>,
+    waited: u64,
+}
+
+struct Simulation<'a> {
+    config: &'a SimulationConfig,
+    center: CallCenter,
+    rng: StdRng,
+    events: BinaryHeap<Reverse<(u64, u64, SimEvent)>>,
+    seq: u64,
+    now: u64,
+    arrivals: usize,
+    active: HashMap<CallId, ActiveCall>,
+    states: HashMap<CallId, CallState>,
+    report: SimulationReport,
+}
+
+/// # Discrete event simulation of a [CallCenter]
+///
+/// Every call comes in for a respondent and goes through the [CallCenter] queue, so the
+/// simulation exercises the same dispatching, escalation and priority rules. The same config
+/// always produces the same report.
+///
+/// Calls are only escalated when there is someone above the employee handling them, so every
+/// call ends up either completed or abandoned.
+pub fn simulate(config: &SimulationConfig) -> Result<SimulationReport, SimulationErr> {
+    config.validate()?;
+    let names: Vec<Vec<String>> = ["Respondent", "Manager", "Director"]
+        .iter()
+        .zip(config.staff.iter())
+        .map(|(role, &count)| (1..=count).map(|i| format!("{} {}", role, i)).collect())
+        .collect();
+    let names: Vec<Vec<&str>> = names
+        .iter()
+        .map(|role| role.iter().map(String::as_str).collect())
+        .collect();
+    let center = CallCenter::from_iters(names[0].clone(), names[1].clone(), names[2].clone());
+
+    let mut simulation = Simulation {
+        config,
+        center,
+        rng: StdRng::seed_from_u64(config.seed),
+        events: BinaryHeap::new(),
+        seq: 0,
+        now: 0,
+        arrivals: 0,
+        active: HashMap::new(),
+        states: HashMap::new(),
+        report: SimulationReport {
+            calls: config.calls,
+            staff: config.staff,
+            ..SimulationReport::default()
+        },
+    };
+    if config.calls > 0 {
+        simulation.schedule(0, SimEvent::Arrival);
+    }
+    Ok(simulation.run())
+}
+
+impl Simulation<'_> {
+    fn run(mut self) -> SimulationReport {
+        while let Some(Reverse((time, _, event))) = self.events.pop() {
+            self.now = time;
+            match event {
+                SimEvent::Arrival => self.arrival(),
+                SimEvent::Finish(id) => self.finish(id),
+                SimEvent::Abandon(id, since) => self.abandon(id, since),
+            }
+        }
+        self.report.waits.sort_unstable();
+        self.report
+    }
+
+    fn schedule(&mut self, delay: u64, event: SimEvent) {
+        self.seq += 1;
+        self.events
+            .push(Reverse((self.now + delay, self.seq, event)));
+    }
+
+    /// Exponentially distributed duration, at least one second
+    fn sample(&mut self, mean: f64) -> u64 {
+        let uniform: f64 = self.rng.gen();
+        (-mean * (1.0 - uniform).ln()).round().max(1.0) as u64
+    }
+
+    fn sample_priority(&mut self) -> Priority {
+        let weights = self.config.priority_weights;
+        let total: u32 = weights.iter().sum();
+        if total == 0 {
+            return Priority::default();
+        }
+        let mut pick = self.rng.gen_range(0..total);
+        let priorities = [
+            Priority::Low,
+            Priority::Normal,
+            Priority::High,
+            Priority::Urgent,
+        ];
+        for (priority, weight) in priorities.iter().zip(weights.iter()) {
+            if pick < *weight {
+                return *priority;
+            }
+            pick -= weight;
+        }
+        unreachable!()
+    }
+
+    fn arrival(&mut self) {
+        self.arrivals += 1;
+        if self.arrivals < self.config.calls {
+            let interval = self.sample(self.config.mean_arrival_interval);
+            self.schedule(interval, SimEvent::Arrival);
+        }
+        let priority = self.sample_priority();
+        let dispatch = self.center.receive_call(Role::Respondent, priority);
+        self.dispatched(dispatch);
+    }
+
+    fn dispatched(&mut self, dispatch: Dispatch) {
+        match dispatch {
+            Dispatch::Answered(call) => self.start(call),
+            Dispatch::Queued(id) => {
+                self.states.entry(id).or_default().queued_since = Some(self.now);
+                if let Some(patience) = self.config.mean_patience {
+                    let patience = self.sample(patience);
+                    self.schedule(patience, SimEvent::Abandon(id, self.now));
+                }
+            }
+        }
+    }
+
+    fn start(&mut self, call: Call) {
+        let state = self.states.entry(call.id()).or_default();
+        if let Some(since) = state.queued_since.take() {
+            state.waited += self.now - since;
+        }
+        let role = call.role() as usize;
+        let handling = self.sample(self.config.mean_handling_time[role]);
+        let can_escalate = self.config.staff[role + 1..].iter().any(|&count| count > 0);
+        let escalate = can_escalate && self.rng.gen_bool(self.config.escalation_probability[role]);
+        let id = call.id();
+        self.active.insert(
+            id,
+            ActiveCall {
+                call,
+                started: self.now,
+                escalate,
+            },
+        );
+        self.schedule(handling, SimEvent::Finish(id));
+    }
+
+    fn finish(&mut self, id: CallId) {
+        let active = self.active.remove(&id).unwrap();
+        self.report.duration = self.now;
+        self.report.busy[active.call.role() as usize] += self.now - active.started;
+
+        let call = if active.escalate {
+            match self.center.escalate(active.call) {
+                Ok((dispatch, picked_up)) => {
+                    self.report.escalations += 1;
+                    self.dispatched(dispatch);
+                    if let Some(call) = picked_up {
+                        self.start(call);
+                    }
+                    return;
+                }
+                Err(call) => call,
+            }
+        } else {
+            active.call
+        };
+
+        let picked_up = self.center.end_call(call);
+        let state = self.states.remove(&id).unwrap_or_default();
+        self.report.completed += 1;
+        self.report.waits.push(state.waited);
+        if let Some(call) = picked_up {
+            self.start(call);
+        }
+    }
+
+    fn abandon(&mut self, id: CallId, since: u64) {
+        let still_waiting = self
+            .states
+            .get(&id)
+            .is_some_and(|state| state.queued_since == Some(since));
+        if still_waiting && self.center.abandon(id) {
+            self.states.remove(&id);
+            self.report.abandoned += 1;
+            self.report.duration = self.now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulation_is_deterministic() {
+        let config = SimulationConfig {
+            seed: 3,
+            priority_weights: [1, 4, 2, 1],
+            ..SimulationConfig::default()
+        };
+        let report = simulate(&config).unwrap();
+        assert_eq!(report, simulate(&config).unwrap());
+        assert_eq!(report.completed + report.abandoned, config.calls);
+        assert!(report.escalations > 0);
+
+        let other = simulate(&SimulationConfig { seed: 4, ..config }).unwrap();
+        assert_ne!(report, other);
+    }
+
+    #[test]
+    fn staffing_reduces_waits() {
+        let busy = SimulationConfig {
+            seed: 11,
+            staff: [2, 1, 1],
+            mean_arrival_interval: 40.0,
+            ..SimulationConfig::default()
+        };
+        let staffed = SimulationConfig {
+            staff: [8, 2, 1],
+            ..busy.clone()
+        };
+        let busy = simulate(&busy).unwrap();
+        let staffed = simulate(&staffed).unwrap();
+
+        assert!(busy.abandonment_rate() > staffed.abandonment_rate());
+        assert!(busy.wait_percentile(90.0) > staffed.wait_percentile(90.0));
+        assert!(busy.mean_wait() > staffed.mean_wait());
+        assert!(busy.utilisation(Role::Respondent) > staffed.utilisation(Role::Respondent));
+        for role in [Role::Respondent, Role::Manager, Role::Director].iter() {
+            assert!((0.0..=1.0).contains(&busy.utilisation(*role)));
+        }
+    }
+
+    #[test]
+    fn no_abandonment_or_escalation() {
+        let config = SimulationConfig {
+            seed: 5,
+            staff: [1, 0, 0],
+            calls: 200,
+            mean_patience: None,
+            escalation_probability: [0.0; 3],
+            ..SimulationConfig::default()
+        };
+        let report = simulate(&config).unwrap();
+        assert_eq!(report.completed, 200);
+        assert_eq!(report.abandoned, 0);
+        assert_eq!(report.escalations, 0);
+        assert_eq!(report.utilisation(Role::Manager), 0.0);
+        // A single respondent is overwhelmed, so most calls wait
+        assert!(report.wait_percentile(50.0).unwrap() > 0);
+        assert!(report.wait_percentile(0.0) <= report.wait_percentile(100.0));
+    }
+
+    #[test]
+    fn escalation_without_anyone_above() {
+        // Respondents would escalate every call, but there are no managers or directors
+        let config = SimulationConfig {
+            seed: 8,
+            staff: [3, 0, 0],
+            calls: 100,
+            mean_patience: None,
+            escalation_probability: [1.0, 1.0, 0.0],
+            ..SimulationConfig::default()
+        };
+        let report = simulate(&config).unwrap();
+        assert_eq!(report.completed, 100);
+        assert_eq!(report.escalations, 0);
+
+        // Directors take the calls escalated to managers, and answer some calls directly when the
+        // respondents are busy
+        let report = simulate(&SimulationConfig {
+            staff: [3, 0, 1],
+            ..config
+        })
+        .unwrap();
+        assert_eq!(report.completed, 100);
+        assert!(report.escalations > 50);
+    }
+
+    #[test]
+    fn invalid_configs() {
+        let config = SimulationConfig {
+            staff: [0; 3],
+            ..SimulationConfig::default()
+        };
+        assert_eq!(simulate(&config), Err(SimulationErr::NoStaff));
+
+        let config = SimulationConfig {
+            escalation_probability: [0.1, 1.5, 0.0],
+            ..SimulationConfig::default()
+        };
+        assert_eq!(
+            simulate(&config),
+            Err(SimulationErr::InvalidProbability {
+                role: Role::Manager,
+                probability: 1.5
+            })
+        );
+        let config = SimulationConfig {
+            escalation_probability: [-0.1, 0.0, 0.0],
+            ..config
+        };
+        assert!(config.validate().is_err());
+        // Directors never escalate, so their probability does not matter
+        let config = SimulationConfig {
+            escalation_probability: [0.1, 0.1, 7.0],
+            ..config
+        };
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn wait_percentiles() {
+        let report = SimulationReport {
+            waits: (1..=10).collect(),
+            ..SimulationReport::default()
+        };
+        assert_eq!(report.wait_percentile(50.0), Some(5));
+        assert_eq!(report.wait_percentile(90.0), Some(9));
+        assert_eq!(report.wait_percentile(95.0), Some(10));
+        assert_eq!(report.wait_percentile(0.0), Some(1));
+        assert_eq!(SimulationReport::default().wait_percentile(50.0), None);
+    }
+}
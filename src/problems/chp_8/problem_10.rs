@@ -1,3 +1,9 @@
+use crate::structures::{Encoding, Grid, NetpbmErr, NetpbmParser};
+use std::io::{self, Read, Write};
+
+mod fill;
+pub use fill::*;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Color {
     Red,
@@ -7,49 +13,124 @@ pub enum Color {
     Orange,
 }
 
-pub fn paint_fill<const N: usize, const M: usize>(
-    screen: &mut [[Color; M]; N],
-    position: (usize, usize),
-    to: Color,
-) {
-    if N == 0 || M == 0 {
-        return;
-    }
-    let (row, col) = position;
-    let from = screen[row][col];
-    if from == to {
-        return;
-    }
-    paint_fill_helper(screen, position, from, to)
+/// Mapping between [Color]s and the RGB values they are stored as in PPM images
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<(Color, [u8; 3])>,
 }
 
-fn paint_fill_helper<const N: usize, const M: usize>(
-    screen: &mut [[Color; M]; N],
-    position: (usize, usize),
-    from: Color,
-    to: Color,
-) {
-    assert!(N != 0 && M != 0, "empty screen");
+impl Palette {
+    pub fn new(colors: Vec<(Color, [u8; 3])>) -> Self {
+        Self { colors }
+    }
 
-    let (row, col) = position;
-    println!("row: {}, col: {}", row, col);
-    screen[row][col] = to;
+    /// RGB value of `color`, black if it is not in the palette
+    pub fn rgb(&self, color: Color) -> [u8; 3] {
+        self.colors
+            .iter()
+            .find(|(c, _)| *c == color)
+            .map_or([0, 0, 0], |(_, rgb)| *rgb)
+    }
 
-    if row + 1 < N && screen[row + 1][col] == from {
-        paint_fill_helper(screen, (row + 1, col), from, to);
+    pub fn color(&self, rgb: [u8; 3]) -> Option<Color> {
+        self.colors
+            .iter()
+            .find(|(_, value)| *value == rgb)
+            .map(|(color, _)| *color)
     }
+}
 
-    if row > 0 && screen[row - 1][col] == from {
-        paint_fill_helper(screen, (row - 1, col), from, to);
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new(vec![
+            (Color::Red, [255, 0, 0]),
+            (Color::Yellow, [255, 255, 0]),
+            (Color::Blue, [0, 0, 255]),
+            (Color::Green, [0, 255, 0]),
+            (Color::Orange, [255, 165, 0]),
+        ])
     }
+}
 
-    if col + 1 < M && screen[row][col + 1] == from {
-        paint_fill_helper(screen, (row, col + 1), from, to);
+/// Writes `screen` as a PPM image with 8 bits samples, so it can be opened in an image viewer.
+pub fn write_ppm<W: Write>(
+    screen: &Grid<Color>,
+    mut writer: W,
+    encoding: Encoding,
+    palette: &Palette,
+) -> io::Result<()> {
+    match encoding {
+        Encoding::Plain => {
+            writeln!(writer, "P3\n{} {}\n255", screen.cols(), screen.rows())?;
+            for row in screen.iter_rows() {
+                let pixels: Vec<String> = row
+                    .iter()
+                    .map(|&color| {
+                        let [r, g, b] = palette.rgb(color);
+                        format!("{} {} {}", r, g, b)
+                    })
+                    .collect();
+                writeln!(writer, "{}", pixels.join("  "))?;
+            }
+        }
+        Encoding::Binary => {
+            write!(writer, "P6\n{} {}\n255\n", screen.cols(), screen.rows())?;
+            for (_, color) in screen.iter() {
+                writer.write_all(&palette.rgb(*color))?;
+            }
+        }
     }
+    writer.flush()
+}
 
-    if col > 0 && screen[row][col - 1] == from {
-        paint_fill_helper(screen, (row, col - 1), from, to);
+/// Reads a plain or binary PPM image, every pixel must be one of the `palette` colors. Samples
+/// are scaled to 8 bits when the image uses a smaller max value.
+pub fn read_ppm<R: Read>(mut reader: R, palette: &Palette) -> Result<Grid<Color>, NetpbmErr> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let mut parser = NetpbmParser::new(&data);
+    let magic = parser.magic()?;
+    if magic != 3 && magic != 6 {
+        return Err(NetpbmErr::InvalidMagic);
     }
+    let (width, height) = (parser.number()?, parser.number()?);
+    let max = parser.number()?;
+    if max == 0 || max > 255 {
+        return Err(NetpbmErr::UnsupportedMaxValue(max));
+    }
+
+    // Dimensions too large for memory cannot match the size of the data anyway
+    let len = width
+        .checked_mul(height)
+        .and_then(|len| len.checked_mul(3))
+        .ok_or(NetpbmErr::InvalidNumber)?;
+    let samples: Vec<usize> = if magic == 6 {
+        let raster = parser.raster(len)?;
+        raster.iter().map(|&sample| sample as usize).collect()
+    } else {
+        (0..len)
+            .map(|_| parser.number())
+            .collect::<Result<_, _>>()?
+    };
+
+    let mut pixels = Vec::with_capacity(len / 3);
+    for rgb in samples.chunks(3) {
+        let mut value = [0; 3];
+        for (channel, &sample) in value.iter_mut().zip(rgb) {
+            if sample > max {
+                return Err(NetpbmErr::InvalidNumber);
+            }
+            *channel = (sample * 255 / max) as u8;
+        }
+        pixels.push(palette.color(value).ok_or(NetpbmErr::UnknownColor(value))?);
+    }
+    Grid::from_vec(height, width, pixels).ok_or(NetpbmErr::InvalidNumber)
+}
+
+/// Fills the region of `screen` containing `position` with `to`, see [Fill] for the other kinds
+/// of fill.
+pub fn paint_fill(screen: &mut Grid<Color>, position: (usize, usize), to: Color) -> FillReport {
+    Fill::new(to).apply(screen, position)
 }
 
 #[cfg(test)]
@@ -57,70 +138,151 @@ mod tests {
     use super::Color::*;
     use super::*;
 
+    fn grid<const N: usize, const M: usize>(rows: [[Color; M]; N]) -> Grid<Color> {
+        Grid::from_rows(rows.iter().map(|row| row.to_vec())).unwrap()
+    }
+
     #[test]
     fn paint_fill_1() {
-        let mut screen = [
+        let mut screen = grid([
             [Blue, Blue, Blue, Yellow],
             [Red, Blue, Blue, Blue],
             [Yellow, Blue, Red, Yellow],
-        ];
+        ]);
         let expected = screen.clone();
-        paint_fill(&mut screen, (0, 0), Blue);
+        assert_eq!(paint_fill(&mut screen, (0, 0), Blue), FillReport::default());
         assert_eq!(screen, expected);
 
-        paint_fill(&mut screen, (0, 0), Orange);
+        let report = paint_fill(&mut screen, (0, 0), Orange);
+        assert_eq!(report.filled, 7);
+        assert_eq!(
+            report.bounds,
+            Some(BoundingBox {
+                top: 0,
+                left: 0,
+                bottom: 2,
+                right: 3
+            })
+        );
         assert_eq!(
             screen,
-            [
+            grid([
                 [Orange, Orange, Orange, Yellow],
                 [Red, Orange, Orange, Orange],
                 [Yellow, Orange, Red, Yellow],
-            ]
+            ])
         )
     }
 
     #[test]
     fn paint_fill_2() {
-        let mut screen = [[]];
+        let mut screen = Grid::new(1, 0, Red);
         paint_fill(&mut screen, (0, 0), Orange);
-        assert_eq!(screen, [[]]);
+        assert_eq!(screen, Grid::new(1, 0, Red));
 
-        let mut screen = [[Red; 5]; 4];
+        let mut screen = Grid::new(4, 5, Red);
         let expected = screen.clone();
         paint_fill(&mut screen, (1, 1), Red);
         assert_eq!(screen, expected);
 
         paint_fill(&mut screen, (1, 1), Orange);
-        assert_eq!(screen, [[Orange; 5]; 4]);
+        assert_eq!(screen, Grid::new(4, 5, Orange));
     }
 
     #[test]
     fn paint_fill_3() {
-        let mut screen = [
+        let mut screen = grid([
             [Red, Red, Blue, Red, Red],
             [Red, Red, Blue, Red, Red],
             [Blue, Blue, Blue, Red, Red],
             [Red, Red, Red, Red, Red],
             [Red, Red, Red, Red, Red],
-        ];
+        ]);
         paint_fill(&mut screen, (0, 0), Blue);
         assert_eq!(
             screen,
-            [
+            grid([
                 [Blue, Blue, Blue, Red, Red],
                 [Blue, Blue, Blue, Red, Red],
                 [Blue, Blue, Blue, Red, Red],
                 [Red, Red, Red, Red, Red],
                 [Red, Red, Red, Red, Red],
-            ]
+            ])
         );
 
-        let mut screen = [[Red; 5]; 4];
+        let mut screen = Grid::new(4, 5, Red);
         let expected = screen.clone();
         paint_fill(&mut screen, (1, 1), Red);
         assert_eq!(screen, expected);
 
         paint_fill(&mut screen, (1, 1), Orange);
-        assert_eq!(screen, [[Orange; 5]; 4]);
+        assert_eq!(screen, Grid::new(4, 5, Orange));
+    }
+
+    #[test]
+    fn ppm_fixture() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/paint_fill.ppm");
+        let palette = Palette::default();
+        let mut screen = read_ppm(std::fs::File::open(path).unwrap(), &palette).unwrap();
+        assert_eq!((screen.rows(), screen.cols()), (5, 5));
+        assert_eq!(screen.row(2), &[Blue, Blue, Blue, Red, Red]);
+
+        paint_fill(&mut screen, (0, 0), Blue);
+        assert_eq!(screen.row(0), &[Blue, Blue, Blue, Red, Red]);
+        assert_eq!(screen.row(3), &[Red; 5]);
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let screen = grid([[Red, Yellow, Blue], [Green, Orange, Red]]);
+        let palette = Palette::default();
+        for &encoding in [Encoding::Plain, Encoding::Binary].iter() {
+            let mut image = Vec::new();
+            write_ppm(&screen, &mut image, encoding, &palette).unwrap();
+            assert_eq!(read_ppm(image.as_slice(), &palette).unwrap(), screen);
+        }
+
+        let mut image = Vec::new();
+        write_ppm(&grid([[Blue]]), &mut image, Encoding::Binary, &palette).unwrap();
+        assert_eq!(image, b"P6\n1 1\n255\n\x00\x00\xFF");
+    }
+
+    #[test]
+    fn ppm_palette_and_errors() {
+        let palette = Palette::new(vec![(Red, [255, 0, 0]), (Blue, [0, 0, 255])]);
+        // Samples are scaled to 8 bits
+        let read = read_ppm(&b"P3 2 1 1 1 0 0 0 0 1"[..], &palette).unwrap();
+        assert_eq!(read, grid([[Red, Blue]]));
+        assert_eq!(palette.rgb(Green), [0, 0, 0]);
+        assert_eq!(palette.color([0, 0, 0]), None);
+
+        assert!(matches!(
+            read_ppm(&b"P3 1 1 255 0 255 0"[..], &palette),
+            Err(NetpbmErr::UnknownColor([0, 255, 0]))
+        ));
+        assert!(matches!(
+            read_ppm(&b"P3 1 1 65535 0 0 0"[..], &palette),
+            Err(NetpbmErr::UnsupportedMaxValue(65535))
+        ));
+        assert!(matches!(
+            read_ppm(&b"P3 1 1 1 2 0 0"[..], &palette),
+            Err(NetpbmErr::InvalidNumber)
+        ));
+        assert!(matches!(
+            read_ppm(&b"P3 1 2 255 0 0 255"[..], &palette),
+            Err(NetpbmErr::UnexpectedEof)
+        ));
+        assert!(matches!(
+            read_ppm(&b"P6 1 1 255\n\xFF"[..], &palette),
+            Err(NetpbmErr::UnexpectedEof)
+        ));
+        assert!(matches!(
+            read_ppm(&b"P6 9999999999 9999999999 255\n"[..], &palette),
+            Err(NetpbmErr::InvalidNumber)
+        ));
+        assert!(matches!(
+            read_ppm(&b"P3 18446744073709551615 1 255"[..], &palette),
+            Err(NetpbmErr::InvalidNumber)
+        ));
     }
 }
@@ -0,0 +1,325 @@
+use super::Color;
+use crate::structures::{Connectivity, Grid};
+
+/// Pixel type a [Fill] can compare against a reference value.
+///
+/// `distance` is 0 for equal pixels, the default implementation only knows about equality so any
+/// other pixel is infinitely far.
+pub trait Pixel: Copy + PartialEq {
+    fn distance(self, other: Self) -> f64 {
+        if self == other {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+macro_rules! impl_numeric_pixel {
+    ($($num:ty),*) => {
+        $(
+            impl Pixel for $num {
+                fn distance(self, other: Self) -> f64 {
+                    (self as f64 - other as f64).abs()
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_pixel!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+/// RGB pixels are as far as their most different channel
+impl Pixel for [u8; 3] {
+    fn distance(self, other: Self) -> f64 {
+        self.iter()
+            .zip(other.iter())
+            .map(|(&a, &b)| (a as f64 - b as f64).abs())
+            .fold(0.0, f64::max)
+    }
+}
+
+impl Pixel for Color {}
+
+/// Smallest rectangle containing every filled pixel, bounds are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: usize,
+    pub right: usize,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> usize {
+        self.right - self.left + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.bottom - self.top + 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FillReport {
+    /// Number of pixels which were painted
+    pub filled: usize,
+    /// `None` if nothing was filled
+    pub bounds: Option<BoundingBox>,
+}
+
+impl FillReport {
+    fn add_span(&mut self, row: usize, left: usize, right: usize) {
+        self.filled += right - left + 1;
+        self.bounds = Some(match self.bounds {
+            None => BoundingBox {
+                top: row,
+                left,
+                bottom: row,
+                right,
+            },
+            Some(bounds) => BoundingBox {
+                top: bounds.top.min(row),
+                left: bounds.left.min(left),
+                bottom: bounds.bottom.max(row),
+                right: bounds.right.max(right),
+            },
+        });
+    }
+}
+
+/// # Scanline fill
+///
+/// By default fills the region of pixels equal to the starting one, connected horizontally or
+/// vertically. The region can also include the pixels within `tolerance` of the starting one
+/// ([Fill::with_tolerance]), or instead be every pixel up to a boundary color
+/// ([Fill::with_boundary]).
+///
+/// Whole horizontal spans are painted at once and only the start of each span of the rows above
+/// and below is pushed on an explicit stack, so the work stack stays small and large images
+/// cannot overflow the call stack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fill<T> {
+    to: T,
+    connectivity: Connectivity,
+    tolerance: f64,
+    boundary: Option<T>,
+}
+
+impl<T: Pixel> Fill<T> {
+    pub fn new(to: T) -> Self {
+        Self {
+            to,
+            connectivity: Connectivity::Four,
+            tolerance: 0.0,
+            boundary: None,
+        }
+    }
+
+    pub fn with_connectivity(self, connectivity: Connectivity) -> Self {
+        Self {
+            connectivity,
+            ..self
+        }
+    }
+
+    /// Maximum [distance][Pixel::distance] to the starting pixel (or the boundary color) for a
+    /// pixel to match it
+    pub fn with_tolerance(self, tolerance: f64) -> Self {
+        Self { tolerance, ..self }
+    }
+
+    /// Boundary fill, which paints every pixel until it reaches pixels matching `boundary`
+    pub fn with_boundary(self, boundary: T) -> Self {
+        Self {
+            boundary: Some(boundary),
+            ..self
+        }
+    }
+
+    /// Fills the region containing `position`, nothing is filled if `position` is outside of
+    /// the grid or on the boundary, or if the region already has the fill color.
+    pub fn apply(&self, grid: &mut Grid<T>, position: (usize, usize)) -> FillReport {
+        let mut report = FillReport::default();
+        let seed = match grid.get(position) {
+            Some(&seed) => seed,
+            None => return report,
+        };
+        if self.boundary.is_none() && self.tolerance == 0.0 && seed.distance(self.to) == 0.0 {
+            return report;
+        }
+        let matches = |pixel: T, reference: T| pixel.distance(reference) <= self.tolerance;
+        let inside = |pixel: T| match self.boundary {
+            Some(boundary) => !matches(pixel, boundary),
+            None => matches(pixel, seed),
+        };
+        // Painted pixels may still look like they are inside, ie. with a tolerance
+        let mut painted = Grid::new(grid.rows(), grid.cols(), false);
+        let fillable =
+            |grid: &Grid<T>, painted: &Grid<bool>, pos| !painted[pos] && inside(grid[pos]);
+        let reach = match self.connectivity {
+            Connectivity::Four => 0,
+            Connectivity::Eight => 1,
+        };
+
+        let mut stack = vec![position];
+        while let Some((row, col)) = stack.pop() {
+            if !fillable(grid, &painted, (row, col)) {
+                continue;
+            }
+            let mut left = col;
+            while left > 0 && fillable(grid, &painted, (row, left - 1)) {
+                left -= 1;
+            }
+            let mut right = col;
+            while right + 1 < grid.cols() && fillable(grid, &painted, (row, right + 1)) {
+                right += 1;
+            }
+            for col in left..=right {
+                grid[(row, col)] = self.to;
+                painted[(row, col)] = true;
+            }
+            report.add_span(row, left, right);
+
+            let from = left.saturating_sub(reach);
+            let to = (right + reach).min(grid.cols() - 1);
+            let rows = [
+                row.checked_sub(1),
+                Some(row + 1).filter(|&r| r < grid.rows()),
+            ];
+            for next in rows.iter().flatten() {
+                let mut in_span = false;
+                for col in from..=to {
+                    let fill = fillable(grid, &painted, (*next, col));
+                    if fill && !in_span {
+                        stack.push((*next, col));
+                    }
+                    in_span = fill;
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(map: &str) -> Grid<u8> {
+        Grid::parse(map, |ch| ch.to_digit(10).map(|d| d as u8)).unwrap()
+    }
+
+    #[test]
+    fn connectivity() {
+        let map = "
+            0100
+            1010
+            0101
+        ";
+        let mut four = digits(map);
+        let report = Fill::new(7).apply(&mut four, (1, 1));
+        assert_eq!(four, digits("0100\n1710\n0101"));
+        assert_eq!(report.filled, 1);
+
+        let mut eight = digits(map);
+        let report = Fill::new(7)
+            .with_connectivity(Connectivity::Eight)
+            .apply(&mut eight, (1, 1));
+        assert_eq!(eight, digits("7177\n1717\n7171"));
+        assert_eq!(report.filled, 7);
+        assert_eq!(
+            report.bounds,
+            Some(BoundingBox {
+                top: 0,
+                left: 0,
+                bottom: 2,
+                right: 3
+            })
+        );
+    }
+
+    #[test]
+    fn tolerance() {
+        let mut grid = digits(
+            "
+            1239
+            2349
+            9999
+            ",
+        );
+        let report = Fill::new(0).with_tolerance(2.0).apply(&mut grid, (0, 0));
+        assert_eq!(grid, digits("0009\n0049\n9999"));
+        assert_eq!(report.filled, 5);
+
+        // The tolerance is relative to the starting pixel, not to the neighbours
+        let mut grid = Grid::from_rows(vec![vec![10.0, 10.4, 10.8, 11.2]]).unwrap();
+        Fill::new(0.0).with_tolerance(0.5).apply(&mut grid, (0, 0));
+        assert_eq!(grid.row(0), &[0.0, 0.0, 10.8, 11.2]);
+
+        let mut rgb = Grid::from_rows(vec![vec![[200, 0, 0], [190, 10, 5], [0, 0, 0]]]).unwrap();
+        let report = Fill::new([0, 255, 0])
+            .with_tolerance(10.0)
+            .apply(&mut rgb, (0, 0));
+        assert_eq!(report.filled, 2);
+        assert_eq!(rgb[(0, 2)], [0, 0, 0]);
+    }
+
+    #[test]
+    fn boundary_fill() {
+        // Paints over every color until it reaches the 1s
+        let mut grid = digits(
+            "
+            0000000
+            0111110
+            0123410
+            0145610
+            0111110
+            ",
+        );
+        let report = Fill::new(9).with_boundary(1).apply(&mut grid, (2, 2));
+        assert_eq!(grid, digits("0000000\n0111110\n0199910\n0199910\n0111110"));
+        assert_eq!(report.filled, 6);
+        assert_eq!(report.bounds.unwrap().width(), 3);
+        assert_eq!(report.bounds.unwrap().height(), 2);
+
+        // Starting on the boundary or outside of the grid fills nothing
+        let report = Fill::new(9).with_boundary(1).apply(&mut grid, (1, 1));
+        assert_eq!(report, FillReport::default());
+        assert_eq!(Fill::new(9).apply(&mut grid, (10, 0)).bounds, None);
+        // Nor does filling a region with its own color
+        assert_eq!(Fill::new(0).apply(&mut grid, (0, 0)), FillReport::default());
+
+        // The fill color matching the region does not loop forever
+        let mut grid = Grid::new(3, 3, 4u8);
+        let report = Fill::new(5).with_tolerance(1.0).apply(&mut grid, (1, 1));
+        assert_eq!(report.filled, 9);
+    }
+
+    #[test]
+    fn large_image() {
+        let size = 2000;
+        let mut grid = Grid::new(size, size, 0u8);
+        // Serpentine walls make the region a single path winding through the whole image
+        for row in (1..size).step_by(2) {
+            let gap = if row % 4 == 1 { size - 1 } else { 0 };
+            for col in 0..size {
+                if col != gap {
+                    grid[(row, col)] = 1;
+                }
+            }
+        }
+        let report = Fill::new(2).apply(&mut grid, (0, 0));
+        assert_eq!(report.filled, size * size / 2 + size / 2);
+        assert_eq!(
+            report.bounds,
+            Some(BoundingBox {
+                top: 0,
+                left: 0,
+                bottom: size - 1,
+                right: size - 1
+            })
+        );
+        assert_eq!(grid[(size - 1, 0)], 2);
+    }
+}
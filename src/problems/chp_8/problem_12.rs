@@ -1,24 +1,31 @@
+use crate::structures::Grid;
 use std::fmt::Display;
 
-#[derive(Debug, Clone, Copy)]
-pub struct QueenBoard<const N: usize> {
-    board: [[Piece; N]; N],
+#[derive(Debug, Clone)]
+pub struct QueenBoard {
+    board: Grid<Piece>,
 }
 
-impl<const N: usize> QueenBoard<N> {
-    pub fn new() -> Self {
+impl QueenBoard {
+    /// Empty board of `size` by `size` cells
+    pub fn new(size: usize) -> Self {
         Self {
-            board: [[Piece::Empty; N]; N],
+            board: Grid::new(size, size, Piece::Empty),
         }
     }
 
+    pub fn size(&self) -> usize {
+        self.board.rows()
+    }
+
     fn diagonals(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        assert!(row < N, "row {} is greater than size of board {}", row, N);
-        assert!(col < N, "col {} is greater than size of board {}", col, N);
+        let n = self.size();
+        assert!(row < n, "row {} is greater than size of board {}", row, n);
+        assert!(col < n, "col {} is greater than size of board {}", col, n);
 
         let row = row as i64;
         let col = col as i64;
-        let size = N as i64;
+        let size = n as i64;
         let diagonals = (1i64..size)
             .map(|d| {
                 [
@@ -35,18 +42,19 @@ impl<const N: usize> QueenBoard<N> {
     }
 
     fn check_valid(&mut self, row: usize, col: usize) -> bool {
-        if row >= N || col >= N {
+        let n = self.size();
+        if row >= n || col >= n {
             return false;
         }
 
-        for r in 0..N {
-            if self.board[r][col] == Piece::Queen {
+        for r in 0..n {
+            if self.board[(r, col)] == Piece::Queen {
                 return false;
             }
         }
 
         for (r, c) in self.diagonals(row, col) {
-            if self.board[r][c] == Piece::Queen {
+            if self.board[(r, c)] == Piece::Queen {
                 return false;
             }
         }
@@ -60,26 +68,26 @@ impl<const N: usize> QueenBoard<N> {
     }
 
     fn valid_boards_helper(&mut self, row: usize, results: &mut Vec<Self>) {
-        if row == N {
+        if row == self.size() {
             let board = self.clone();
             results.push(board);
             return;
         }
 
-        for col in 0..N {
+        for col in 0..self.size() {
             if self.check_valid(row, col) {
-                self.board[row][col] = Piece::Queen;
+                self.board[(row, col)] = Piece::Queen;
                 self.valid_boards_helper(row + 1, results);
-                self.board[row][col] = Piece::Empty;
+                self.board[(row, col)] = Piece::Empty;
             }
         }
     }
 }
 
-impl<const N: usize> Display for QueenBoard<N> {
+impl Display for QueenBoard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let dividor = "-".repeat((self.board.len() - 1) * 4 + 5);
-        for row in &self.board {
+        let dividor = "-".repeat(self.size().saturating_sub(1) * 4 + 5);
+        for row in self.board.iter_rows() {
             let row: Vec<String> = row.iter().map(|piece| format!("{}", piece)).collect();
             let row = "| ".to_owned() + &row.join(" | ") + " |";
             writeln!(f, "{}", dividor)?;
@@ -113,36 +121,46 @@ mod tests {
     /// results based on the following table:
     /// http://www.durangobill.com/N_Queens.html
     fn valid_queens_test() {
-        let mut board: QueenBoard<1> = QueenBoard::new();
+        let mut board = QueenBoard::new(1);
         let valid_boards = board.valid_boards();
         assert_eq!(valid_boards.len(), 1);
 
-        let mut board: QueenBoard<2> = QueenBoard::new();
+        let mut board = QueenBoard::new(2);
         let valid_boards = board.valid_boards();
         assert_eq!(valid_boards.len(), 0);
 
-        let mut board: QueenBoard<4> = QueenBoard::new();
+        let mut board = QueenBoard::new(4);
         let valid_boards = board.valid_boards();
         assert_eq!(valid_boards.len(), 2);
 
-        let mut board: QueenBoard<5> = QueenBoard::new();
+        let mut board = QueenBoard::new(5);
         let valid_boards = board.valid_boards();
         assert_eq!(valid_boards.len(), 10);
 
-        let mut board: QueenBoard<6> = QueenBoard::new();
+        let mut board = QueenBoard::new(6);
         let valid_boards = board.valid_boards();
         assert_eq!(valid_boards.len(), 4);
 
-        let mut board: QueenBoard<7> = QueenBoard::new();
+        let mut board = QueenBoard::new(7);
         let valid_boards = board.valid_boards();
         assert_eq!(valid_boards.len(), 40);
 
-        let mut board: QueenBoard<8> = QueenBoard::new();
+        let mut board = QueenBoard::new(8);
         let valid_boards = board.valid_boards();
         assert_eq!(valid_boards.len(), 92);
 
-        let mut board: QueenBoard<9> = QueenBoard::new();
+        let mut board = QueenBoard::new(9);
         let valid_boards = board.valid_boards();
         assert_eq!(valid_boards.len(), 352);
     }
+
+    #[test]
+    fn display_board() {
+        let mut board = QueenBoard::new(4);
+        let first = &board.valid_boards()[0];
+        let rendered = first.to_string();
+        assert_eq!(rendered.lines().count(), 4 * 2 + 1);
+        assert_eq!(rendered.matches('♕').count(), 4);
+        assert_eq!(rendered.lines().nth(1), Some("|   | ♕ |   |   |"));
+    }
 }
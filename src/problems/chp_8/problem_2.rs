@@ -1,85 +1,129 @@
-use std::collections::HashSet;
-use std::iter::FromIterator;
+use crate::structures::Grid;
 
-#[derive(Debug, PartialEq, Eq)]
+mod paths;
+pub use paths::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Direction {
     Up,
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
-/// Very naive solution using normal dfs to find the path. Since shortest path is not required, dfs
-/// works well and is simple. I'm prioritizing Right and Down moves as a simple heuristic since
-/// they get us closer to the destination.
-///
-/// Alternative solution with similar complexity would be a 2 point BFS search, and this would also
-/// give us the shortest path.
-pub fn find_path_in_grid<const N: usize, const M: usize>(
-    grid: &[[(); M]; N],
-    blocked: &[(usize, usize)],
-) -> Option<Vec<Direction>> {
-    if grid.is_empty() || grid.first().is_none() {
-        return None;
+impl Direction {
+    /// Row and column offsets of a single move
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (-1, 1),
+            Direction::DownLeft => (1, -1),
+            Direction::DownRight => (1, 1),
+        }
     }
 
-    let mut blocked = HashSet::from_iter(blocked.iter().map(|n| *n));
-    let mut path: Vec<Direction> = Vec::new();
-    if dfs_grid(grid, &mut blocked, (0, 0), &mut path) {
-        return Some(path);
+    /// Position reached by moving from `position`, `None` if it is outside of `grid`
+    fn step<T>(self, grid: &Grid<T>, position: (usize, usize)) -> Option<(usize, usize)> {
+        let (d_row, d_col) = self.offset();
+        let next = (
+            position.0.checked_add_signed(d_row)?,
+            position.1.checked_add_signed(d_col)?,
+        );
+        if grid.contains(next) {
+            Some(next)
+        } else {
+            None
+        }
     }
-    None
 }
 
-fn dfs_grid<const N: usize, const M: usize>(
-    grid: &[[(); M]; N],
-    blocked: &mut HashSet<(usize, usize)>,
-    position: (usize, usize),
-    path: &mut Vec<Direction>,
-) -> bool {
-    assert!(grid.len() > 0, "grid should not be empty");
-    let (row, col) = position;
-
-    if row + 1 == M && col + 1 == N {
-        return true;
-    }
+/// Moves the robot is allowed to make
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Moves {
+    /// Only towards the destination, so every path has the same length
+    RightDown,
+    #[default]
+    FourWay,
+    /// Including diagonal moves, which can go between two blocked cells
+    EightWay,
+}
 
-    // If the cell is blocked remove the last element from it;
-    if blocked.contains(&position) {
-        path.pop();
-        return false;
+impl Moves {
+    /// Allowed directions, the ones getting closer to the destination first
+    pub fn directions(self) -> &'static [Direction] {
+        use Direction::*;
+        match self {
+            Moves::RightDown => &[Right, Down],
+            Moves::FourWay => &[Right, Down, Left, Up],
+            Moves::EightWay => &[DownRight, Right, Down, UpRight, DownLeft, Left, Up, UpLeft],
+        }
     }
+}
 
-    blocked.insert(position);
+/// Very naive solution using normal dfs to find the path. Since shortest path is not required, dfs
+/// works well and is simple. I'm prioritizing Right and Down moves as a simple heuristic since
+/// they get us closer to the destination.
+///
+/// Cells set to `true` are blocked, the path goes from the top left to the bottom right corner.
+///
+/// [shortest_path] uses a BFS search instead, which gives us the shortest path.
+pub fn find_path_in_grid(grid: &Grid<bool>) -> Option<Vec<Direction>> {
+    find_path_in_grid_with(grid, Moves::FourWay)
+}
 
-    if row + 1 < M {
-        path.push(Direction::Right);
-        if dfs_grid(grid, blocked, (row + 1, col), path) {
-            return true;
-        }
+/// Same as [find_path_in_grid], only making the given `moves`
+///
+/// The search uses an explicit stack rather than recursion, so the size of the grid is not limited
+/// by the size of the call stack.
+pub fn find_path_in_grid_with(grid: &Grid<bool>, moves: Moves) -> Option<Vec<Direction>> {
+    if grid.is_empty() || grid[(0, 0)] {
+        return None;
     }
-
-    if col + 1 < N {
-        path.push(Direction::Down);
-        if dfs_grid(grid, blocked, (row, col + 1), path) {
-            return true;
-        }
+    if is_destination(grid, (0, 0)) {
+        return Some(Vec::new());
     }
 
-    if row >= 1 {
-        path.push(Direction::Left);
-        if dfs_grid(grid, blocked, (row - 1, col), path) {
-            return true;
+    let directions = moves.directions();
+    let mut visited = Grid::new(grid.rows(), grid.cols(), false);
+    let mut path: Vec<Direction> = Vec::new();
+    // Cells of the current path and the next direction to try from each one, like in AllPaths
+    let mut stack = vec![((0, 0), 0)];
+    visited[(0, 0)] = true;
+    while let Some(frame) = stack.last_mut() {
+        let (position, idx) = *frame;
+        if idx == directions.len() {
+            // Dead end, the cell stays visited since no path goes through it
+            stack.pop();
+            path.pop();
+            continue;
         }
-    }
-
-    if col >= 1 {
-        path.push(Direction::Up);
-        if dfs_grid(grid, blocked, (row, col - 1), path) {
-            return true;
+        frame.1 += 1;
+
+        let direction = directions[idx];
+        let next = match direction.step(grid, position) {
+            Some(next) if !grid[next] && !visited[next] => next,
+            _ => continue,
+        };
+        path.push(direction);
+        if is_destination(grid, next) {
+            return Some(path);
         }
+        visited[next] = true;
+        stack.push((next, 0));
     }
-    false
+    None
+}
+
+fn is_destination<T>(grid: &Grid<T>, (row, col): (usize, usize)) -> bool {
+    row + 1 == grid.rows() && col + 1 == grid.cols()
 }
 
 #[cfg(test)]
@@ -87,59 +131,110 @@ mod tests {
     use super::Direction::*;
     use super::*;
 
-    /// 0 1 0
-    /// 0 1 0
-    /// 0 0 0
+    fn grid(map: &str) -> Grid<bool> {
+        Grid::parse(map, |ch| match ch {
+            '0' => Some(false),
+            '1' => Some(true),
+            _ => None,
+        })
+        .unwrap()
+    }
+
     #[test]
     fn find_path_in_grid_1() {
-        let grid = [[(); 3]; 3];
-        let blocked = [(1, 0), (1, 1)];
+        let grid = grid(
+            "
+            010
+            010
+            000
+            ",
+        );
         assert_eq!(
-            find_path_in_grid(&grid, &blocked),
+            find_path_in_grid(&grid),
             Some(vec![Down, Down, Right, Right])
         )
     }
 
     #[test]
     fn find_path_in_grid_2() {
-        let grid = [[(); 3]; 3];
-        let blocked = [(1, 0), (1, 1), (1, 2)];
-        assert_eq!(find_path_in_grid(&grid, &blocked), None);
-
-        let grid = [[(); 3]; 0];
-        let blocked = [];
-        assert_eq!(find_path_in_grid(&grid, &blocked), None);
-
-        let grid = [[(); 0]; 3];
-        let blocked = [];
-        assert_eq!(find_path_in_grid(&grid, &blocked), None)
+        let walled = grid(
+            "
+            010
+            010
+            010
+            ",
+        );
+        assert_eq!(find_path_in_grid(&walled), None);
+
+        assert_eq!(find_path_in_grid(&Grid::new(0, 3, false)), None);
+        assert_eq!(find_path_in_grid(&Grid::new(3, 0, false)), None);
+
+        // Blocked start or destination
+        assert_eq!(find_path_in_grid(&grid("10\n00")), None);
+        assert_eq!(find_path_in_grid(&grid("00\n01")), None);
+        assert_eq!(find_path_in_grid(&grid("0")), Some(vec![]));
     }
 
     #[test]
-    /// 0 0 1 0
-    /// 1 0 0 0
-    /// 1 1 1 0
-    /// 0 0 0 0
-    /// 0 1 1 1
-    /// 0 0 0 0
     fn find_path_in_grid_3() {
-        let grid = [[(); 4]; 6];
-        let blocked = [
-            (2, 0),
-            (0, 1),
-            (0, 2),
-            (1, 2),
-            (2, 2),
-            (1, 4),
-            (2, 4),
-            (3, 4),
-        ];
+        let grid = grid(
+            "
+            0010
+            1000
+            1110
+            0000
+            0111
+            0000
+            ",
+        );
         assert_eq!(
-            find_path_in_grid(&grid, &blocked),
+            find_path_in_grid(&grid),
             Some(vec![
                 Right, Down, Right, Right, Down, Down, Left, Left, Left, Down, Down, Right, Right,
                 Right
             ])
         )
     }
+
+    #[test]
+    fn find_path_with_dead_ends() {
+        // Going right first leads to a dead end, which must not be part of the path
+        let grid = grid(
+            "
+            0000
+            0110
+            0011
+            1000
+            ",
+        );
+        assert_eq!(
+            find_path_in_grid(&grid),
+            Some(vec![Down, Down, Right, Down, Right, Right])
+        );
+
+        let open = Grid::new(7, 9, false);
+        let path = find_path_in_grid(&open).unwrap();
+        assert_eq!(path.len(), 6 + 8);
+    }
+
+    #[test]
+    fn large_maze() {
+        let size = 1001;
+        let mut maze = Grid::new(size, size, false);
+        // Serpentine walls make the only path wind through the whole grid
+        for row in (1..size).step_by(2) {
+            let gap = if row % 4 == 1 { size - 1 } else { 0 };
+            for col in 0..size {
+                if col != gap {
+                    maze[(row, col)] = true;
+                }
+            }
+        }
+        let path = find_path_in_grid(&maze).unwrap();
+        assert_eq!(path.len(), (size + 1) / 2 * (size - 1) + (size - 1));
+        assert_eq!(
+            path.iter().filter(|&&direction| direction == Down).count(),
+            size - 1
+        );
+    }
 }
@@ -0,0 +1,299 @@
+use super::{is_destination, Direction, Moves};
+use crate::problems::chp_2::list_number::ListNumber;
+use crate::structures::Grid;
+use std::collections::VecDeque;
+
+/// Shortest path from the top left to the bottom right corner using a BFS search, `None` if the
+/// destination cannot be reached. Cells set to `true` are blocked.
+pub fn shortest_path(grid: &Grid<bool>, moves: Moves) -> Option<Vec<Direction>> {
+    if grid.is_empty() || grid[(0, 0)] {
+        return None;
+    }
+    // Move which first reached each cell
+    let mut reached_by: Grid<Option<Direction>> = Grid::new(grid.rows(), grid.cols(), None);
+    let mut queue = VecDeque::from(vec![(0, 0)]);
+    let mut found = is_destination(grid, (0, 0));
+
+    while let Some(position) = queue.pop_front() {
+        if found {
+            break;
+        }
+        for &direction in moves.directions() {
+            let next = match direction.step(grid, position) {
+                Some(next) if !grid[next] && next != (0, 0) && reached_by[next].is_none() => next,
+                _ => continue,
+            };
+            reached_by[next] = Some(direction);
+            if is_destination(grid, next) {
+                found = true;
+                break;
+            }
+            queue.push_back(next);
+        }
+    }
+    if !found {
+        return None;
+    }
+
+    let mut path = Vec::new();
+    let mut position = (grid.rows() - 1, grid.cols() - 1);
+    while let Some(direction) = reached_by[position] {
+        path.push(direction);
+        let (d_row, d_col) = direction.offset();
+        position = (
+            position.0.wrapping_add_signed(-d_row),
+            position.1.wrapping_add_signed(-d_col),
+        );
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Number of distinct paths only moving right and down, `None` if it does not fit in a `u128`.
+/// [count_paths_exact] never overflows.
+pub fn count_paths(grid: &Grid<bool>) -> Option<u128> {
+    count_monotone_paths(grid, Some(0), Some(1), |a, b| match (a, b) {
+        (Some(a), Some(b)) => a.checked_add(*b),
+        _ => None,
+    })
+}
+
+/// Same as [count_paths] but counting with a [ListNumber], which has no upper bound.
+pub fn count_paths_exact(grid: &Grid<bool>) -> ListNumber {
+    count_monotone_paths(grid, ListNumber::zero(), ListNumber::from(1u128), |a, b| {
+        a + b
+    })
+}
+
+/// Dynamic programming over the rows, the number of paths reaching a cell being the sum of the
+/// paths reaching the cells above and on its left.
+fn count_monotone_paths<C: Clone, F: Fn(&C, &C) -> C>(
+    grid: &Grid<bool>,
+    zero: C,
+    one: C,
+    add: F,
+) -> C {
+    if grid.is_empty() {
+        return zero;
+    }
+    let mut counts = vec![zero.clone(); grid.cols()];
+    for (row, cells) in grid.iter_rows().enumerate() {
+        for (col, &blocked) in cells.iter().enumerate() {
+            counts[col] = if blocked {
+                zero.clone()
+            } else if row == 0 && col == 0 {
+                one.clone()
+            } else if col == 0 {
+                counts[col].clone()
+            } else {
+                add(&counts[col], &counts[col - 1])
+            };
+        }
+    }
+    counts.pop().unwrap()
+}
+
+/// Every path from the top left to the bottom right corner which does not go through the same
+/// cell twice, see [AllPaths].
+pub fn all_paths(grid: &Grid<bool>, moves: Moves) -> AllPaths<'_> {
+    AllPaths::new(grid, moves)
+}
+
+/// # Iterator over every path
+///
+/// Depth first search with an explicit stack, so the paths come in the same order as the ones
+/// [find_path_in_grid_with][super::find_path_in_grid_with] tries and the first one is the path it
+/// returns. The number of paths grows exponentially with the size of the grid.
+pub struct AllPaths<'a> {
+    grid: &'a Grid<bool>,
+    moves: Moves,
+    visited: Grid<bool>,
+    /// Cells of the current path, with the index of the next direction to try from each of them
+    stack: Vec<((usize, usize), usize)>,
+    path: Vec<Direction>,
+    /// The start is the destination, so the only path is empty
+    empty_path: bool,
+}
+
+impl<'a> AllPaths<'a> {
+    fn new(grid: &'a Grid<bool>, moves: Moves) -> Self {
+        let mut paths = Self {
+            grid,
+            moves,
+            visited: Grid::new(grid.rows(), grid.cols(), false),
+            stack: Vec::new(),
+            path: Vec::new(),
+            empty_path: false,
+        };
+        if grid.is_empty() || grid[(0, 0)] {
+            return paths;
+        }
+        if is_destination(grid, (0, 0)) {
+            paths.empty_path = true;
+        } else {
+            paths.visited[(0, 0)] = true;
+            paths.stack.push(((0, 0), 0));
+        }
+        paths
+    }
+}
+
+impl Iterator for AllPaths<'_> {
+    type Item = Vec<Direction>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.empty_path {
+            self.empty_path = false;
+            return Some(Vec::new());
+        }
+        let directions = self.moves.directions();
+        while let Some(frame) = self.stack.last_mut() {
+            let (position, idx) = *frame;
+            if idx == directions.len() {
+                self.stack.pop();
+                self.visited[position] = false;
+                self.path.pop();
+                continue;
+            }
+            frame.1 += 1;
+
+            let direction = directions[idx];
+            let next = match direction.step(self.grid, position) {
+                Some(next) if !self.grid[next] && !self.visited[next] => next,
+                _ => continue,
+            };
+            if is_destination(self.grid, next) {
+                let mut path = self.path.clone();
+                path.push(direction);
+                return Some(path);
+            }
+            self.visited[next] = true;
+            self.path.push(direction);
+            self.stack.push((next, 0));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::find_path_in_grid_with;
+    use super::Direction::*;
+    use super::*;
+
+    fn grid(map: &str) -> Grid<bool> {
+        Grid::parse(map, |ch| match ch {
+            '.' => Some(false),
+            '#' => Some(true),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let maze = grid(
+            "
+            ..#....
+            .##.##.
+            .#..#..
+            ...##.#
+            ##.....
+            ",
+        );
+        let path = shortest_path(&maze, Moves::FourWay).unwrap();
+        assert_eq!(path.len(), 10);
+        assert_eq!(path[..4], [Down, Down, Down, Right]);
+        // The depth first search finds a longer path
+        let dfs = find_path_in_grid_with(&maze, Moves::FourWay).unwrap();
+        assert!(dfs.len() >= path.len());
+
+        let diagonal = shortest_path(&maze, Moves::EightWay).unwrap();
+        assert_eq!(diagonal.len(), 8);
+        assert_eq!(shortest_path(&maze, Moves::RightDown).unwrap().len(), 10);
+
+        // Going back left is needed
+        let winding = grid("..\n#.\n..\n.#\n..");
+        assert_eq!(shortest_path(&winding, Moves::RightDown), None);
+        assert_eq!(
+            shortest_path(&winding, Moves::FourWay),
+            Some(vec![Right, Down, Down, Left, Down, Down, Right])
+        );
+
+        let open = Grid::new(4, 6, false);
+        assert_eq!(shortest_path(&open, Moves::RightDown).unwrap().len(), 8);
+        assert_eq!(shortest_path(&open, Moves::EightWay).unwrap().len(), 5);
+        assert_eq!(shortest_path(&grid("."), Moves::FourWay), Some(vec![]));
+        assert_eq!(shortest_path(&grid("#."), Moves::FourWay), None);
+        assert_eq!(shortest_path(&grid(".#"), Moves::FourWay), None);
+        assert_eq!(shortest_path(&Grid::new(0, 0, false), Moves::FourWay), None);
+    }
+
+    #[test]
+    fn path_counts() {
+        assert_eq!(count_paths(&Grid::new(3, 3, false)), Some(6));
+        assert_eq!(count_paths(&grid("...\n.#.\n...")), Some(2));
+        assert_eq!(count_paths(&grid("..\n##\n..")), Some(0));
+        assert_eq!(count_paths(&grid("#.\n..")), Some(0));
+        assert_eq!(count_paths(&grid(".")), Some(1));
+        assert_eq!(count_paths(&Grid::new(0, 4, false)), Some(0));
+
+        // 64 choose 32 paths
+        let open = Grid::new(33, 33, false);
+        assert_eq!(count_paths(&open), Some(1_832_624_140_942_590_534));
+        assert_eq!(
+            count_paths_exact(&open),
+            ListNumber::from(1_832_624_140_942_590_534u128)
+        );
+    }
+
+    #[test]
+    fn path_count_overflow() {
+        // 138 choose 69 paths is larger than u128::MAX
+        let open = Grid::new(70, 70, false);
+        assert_eq!(count_paths(&open), None);
+        assert_eq!(
+            count_paths_exact(&open).to_string(),
+            "23623985175715118288974865541854103729000"
+        );
+
+        // Overflowing counts which cannot reach the destination do not matter
+        let mut walled = open.clone();
+        for row in 1..70 {
+            walled[(row, 68)] = true;
+        }
+        assert_eq!(count_paths(&walled), Some(1));
+    }
+
+    #[test]
+    fn all_paths_iterator() {
+        let open = Grid::new(3, 3, false);
+        let monotone: Vec<_> = all_paths(&open, Moves::RightDown).collect();
+        assert_eq!(monotone.len(), 6);
+        assert_eq!(monotone[0], vec![Right, Right, Down, Down]);
+        assert_eq!(monotone[5], vec![Down, Down, Right, Right]);
+
+        let blocked = grid("...\n.#.\n...");
+        assert_eq!(
+            all_paths(&blocked, Moves::RightDown).count() as u128,
+            count_paths(&blocked).unwrap()
+        );
+        // Without revisiting a cell, a 3x3 grid has 12 paths between opposite corners
+        assert_eq!(all_paths(&open, Moves::FourWay).count(), 12);
+        assert_eq!(
+            all_paths(&open, Moves::FourWay).next(),
+            find_path_in_grid_with(&open, Moves::FourWay)
+        );
+        assert!(all_paths(&open, Moves::EightWay).any(|path| path == vec![DownRight, DownRight]));
+
+        assert_eq!(
+            all_paths(&grid("."), Moves::FourWay).collect::<Vec<_>>(),
+            vec![vec![]]
+        );
+        assert_eq!(all_paths(&grid(".#"), Moves::FourWay).next(), None);
+        assert_eq!(
+            all_paths(&Grid::new(0, 0, false), Moves::FourWay).next(),
+            None
+        );
+    }
+}
@@ -1,9 +1,13 @@
 mod graphs;
+mod grid;
 mod linkedlist;
+mod netpbm;
 mod stack;
 mod trees;
 
 pub use graphs::*;
+pub use grid::*;
 pub use linkedlist::*;
+pub use netpbm::*;
 pub use stack::*;
 pub use trees::*;
@@ -0,0 +1,676 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+/// Which cells are adjacent to a cell of a [Grid]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// Up, down, left and right
+    #[default]
+    Four,
+    /// Including the diagonals
+    Eight,
+}
+
+impl Connectivity {
+    /// Row and column offsets of the neighbours, the first four being the same for both
+    fn offsets(self) -> &'static [(isize, isize)] {
+        const OFFSETS: [(isize, isize); 8] = [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+            (1, 1),
+        ];
+        match self {
+            Connectivity::Four => &OFFSETS[..4],
+            Connectivity::Eight => &OFFSETS,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridErr {
+    /// A row does not have as many cells as the first one
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The character at `row`, `col` does not stand for any cell
+    InvalidCell { row: usize, col: usize, ch: char },
+}
+
+impl fmt::Display for GridErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {} has {} cells instead of {}", row, found, expected),
+            Self::InvalidCell { row, col, ch } => {
+                write!(f, "invalid cell {:?} at row {}, col {}", ch, row, col)
+            }
+        }
+    }
+}
+
+/// # Heap backed 2D grid
+///
+/// Cells are stored row after row and addressed by `(row, col)`, the origin being the top left
+/// corner. Unlike `[[T; M]; N]` arrays the size of the grid is only known at runtime, so it can
+/// come from parsed input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Grid of `rows` by `cols` cells, all set to `value`
+    ///
+    /// # Panics
+    /// Like [Vec::with_capacity], if the number of cells overflows a `usize`.
+    pub fn new(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        let len = rows.checked_mul(cols).expect("grid size overflow");
+        Self {
+            rows,
+            cols,
+            cells: vec![value; len],
+        }
+    }
+
+    /// Grid of `rows` by `cols` cells built from a row major vector, `None` if `cells` does not
+    /// have exactly `rows * cols` elements.
+    pub fn from_vec(rows: usize, cols: usize, cells: Vec<T>) -> Option<Self> {
+        if rows.checked_mul(cols) != Some(cells.len()) {
+            return None;
+        }
+        Some(Self { rows, cols, cells })
+    }
+
+    /// Grid made of the given rows, which must all have the same length
+    pub fn from_rows<R: IntoIterator<Item = T>, I: IntoIterator<Item = R>>(
+        rows: I,
+    ) -> Result<Self, GridErr> {
+        let mut grid = Self {
+            rows: 0,
+            cols: 0,
+            cells: Vec::new(),
+        };
+        for (row, cells) in rows.into_iter().enumerate() {
+            let len = grid.cells.len();
+            grid.cells.extend(cells);
+            let found = grid.cells.len() - len;
+            if row == 0 {
+                grid.cols = found;
+            } else if found != grid.cols {
+                return Err(GridErr::Ragged {
+                    row,
+                    expected: grid.cols,
+                    found,
+                });
+            }
+            grid.rows += 1;
+        }
+        Ok(grid)
+    }
+
+    /// Parses an ASCII map, one line per row and one character per cell.
+    ///
+    /// Lines are trimmed and blank lines ignored, so maps can be indented multiline strings, but
+    /// whitespace cannot stand for a cell.
+    pub fn parse<F: FnMut(char) -> Option<T>>(map: &str, mut cell: F) -> Result<Self, GridErr> {
+        let mut rows = Vec::new();
+        for (row, line) in map
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .enumerate()
+        {
+            let cells = line
+                .chars()
+                .enumerate()
+                .map(|(col, ch)| cell(ch).ok_or(GridErr::InvalidCell { row, col, ch }))
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.push(cells);
+        }
+        Self::from_rows(rows)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Whether the grid has no cells
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, (row, col): (usize, usize)) -> bool {
+        row < self.rows && col < self.cols
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        if !self.contains(position) {
+            return None;
+        }
+        self.cells.get(position.0 * self.cols + position.1)
+    }
+
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        if !self.contains(position) {
+            return None;
+        }
+        self.cells.get_mut(position.0 * self.cols + position.1)
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Every row from top to bottom, so a grid without columns still has [rows][Self::rows]
+    /// empty rows
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.rows).map(move |row| self.row(row))
+    }
+
+    /// Every cell with its position, row after row
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let cols = self.cols;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(idx, cell)| ((idx / cols, idx % cols), cell))
+    }
+
+    /// Every position, row after row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |row| (0..cols).map(move |col| (row, col)))
+    }
+
+    /// Positions adjacent to `position` which are inside the grid
+    pub fn neighbours(
+        &self,
+        position: (usize, usize),
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (rows, cols) = (self.rows, self.cols);
+        connectivity
+            .offsets()
+            .iter()
+            .filter_map(move |&(d_row, d_col)| {
+                let row = position.0.checked_add_signed(d_row)?;
+                let col = position.1.checked_add_signed(d_col)?;
+                if row < rows && col < cols {
+                    Some((row, col))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// View of `rows` by `cols` cells starting at `origin`, clipped to the grid
+    pub fn view(&self, origin: (usize, usize), rows: usize, cols: usize) -> GridView<'_, T> {
+        GridView::new(self, (0, 0), (self.rows, self.cols)).view(origin, rows, cols)
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            rows: self.rows,
+            cols: self.cols,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+/// # Matrix operations
+///
+/// Square grids are rotated, transposed and flipped in place, rectangular ones need a new buffer
+/// to transpose (and so to rotate by a quarter turn) since their shape changes.
+impl<T: Clone> Grid<T> {
+    pub fn to_rows(&self) -> Vec<Vec<T>> {
+        self.iter_rows().map(<[T]>::to_vec).collect()
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Swaps rows and columns, in place for square grids
+    pub fn transpose(&mut self) {
+        if self.is_square() {
+            for row in 0..self.rows {
+                for col in row + 1..self.cols {
+                    self.cells
+                        .swap(row * self.cols + col, col * self.cols + row);
+                }
+            }
+            return;
+        }
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for col in 0..self.cols {
+            for row in 0..self.rows {
+                cells.push(self.cells[row * self.cols + col].clone());
+            }
+        }
+        self.cells = cells;
+        std::mem::swap(&mut self.rows, &mut self.cols);
+    }
+
+    /// Mirrors the grid left to right
+    pub fn flip_horizontal(&mut self) {
+        if self.cols == 0 {
+            return;
+        }
+        for row in self.cells.chunks_mut(self.cols) {
+            row.reverse();
+        }
+    }
+
+    /// Mirrors the grid top to bottom
+    pub fn flip_vertical(&mut self) {
+        for row in 0..self.rows / 2 {
+            let other = self.rows - row - 1;
+            for col in 0..self.cols {
+                self.cells
+                    .swap(row * self.cols + col, other * self.cols + col);
+            }
+        }
+    }
+
+    /// Quarter turn clockwise
+    pub fn rotate_90(&mut self) {
+        self.transpose();
+        self.flip_horizontal();
+    }
+
+    /// Always in place, whatever the shape of the grid
+    pub fn rotate_180(&mut self) {
+        self.cells.reverse();
+    }
+
+    /// Quarter turn counter clockwise
+    pub fn rotate_270(&mut self) {
+        self.transpose();
+        self.flip_vertical();
+    }
+}
+
+impl<T: Copy + Default + PartialEq> Grid<T> {
+    /// Sets the whole row and column of every zero cell to zero, `T::default()` being the zero.
+    ///
+    /// The first row and column record which columns and rows to clear, so only two flags of extra
+    /// space are needed.
+    pub fn zero_rows_and_cols(&mut self) {
+        if self.is_empty() {
+            return;
+        }
+        let zero = T::default();
+        let first_row_zero = self.row(0).contains(&zero);
+        let first_col_zero = (0..self.rows).any(|row| self[(row, 0)] == zero);
+
+        for row in 1..self.rows {
+            for col in 1..self.cols {
+                if self[(row, col)] == zero {
+                    self[(row, 0)] = zero;
+                    self[(0, col)] = zero;
+                }
+            }
+        }
+        for row in 1..self.rows {
+            for col in 1..self.cols {
+                if self[(row, 0)] == zero || self[(0, col)] == zero {
+                    self[(row, col)] = zero;
+                }
+            }
+        }
+
+        if first_row_zero {
+            self.row_mut(0).fill(zero);
+        }
+        if first_col_zero {
+            for row in 0..self.rows {
+                self[(row, 0)] = zero;
+            }
+        }
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = GridErr;
+
+    fn from_str(map: &str) -> Result<Self, Self::Err> {
+        Self::parse(map, Some)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &Self::Output {
+        self.get(position).unwrap_or_else(|| {
+            panic!(
+                "position {:?} is outside of the {}x{} grid",
+                position, self.rows, self.cols
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, position: (usize, usize)) -> &mut Self::Output {
+        let (rows, cols) = (self.rows, self.cols);
+        self.get_mut(position).unwrap_or_else(|| {
+            panic!(
+                "position {:?} is outside of the {}x{} grid",
+                position, rows, cols
+            )
+        })
+    }
+}
+
+/// Rows separated by new lines, without any separator between the cells of a row
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..self.rows {
+            for cell in self.row(row) {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rectangular part of a [Grid], positions are relative to the top left corner of the view.
+#[derive(Debug, Clone, Copy)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    origin: (usize, usize),
+    rows: usize,
+    cols: usize,
+}
+
+impl<'a, T> GridView<'a, T> {
+    fn new(grid: &'a Grid<T>, origin: (usize, usize), (rows, cols): (usize, usize)) -> Self {
+        Self {
+            grid,
+            origin,
+            rows,
+            cols,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, (row, col): (usize, usize)) -> Option<&'a T> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        self.grid.get((self.origin.0 + row, self.origin.1 + col))
+    }
+
+    pub fn row(&self, row: usize) -> &'a [T] {
+        assert!(row < self.rows, "row {} is outside of the view", row);
+        let start = self.origin.1;
+        &self.grid.row(self.origin.0 + row)[start..start + self.cols]
+    }
+
+    /// View of `rows` by `cols` cells starting at `origin`, clipped to this view
+    pub fn view(&self, origin: (usize, usize), rows: usize, cols: usize) -> GridView<'a, T> {
+        let (row, col) = (origin.0.min(self.rows), origin.1.min(self.cols));
+        Self::new(
+            self.grid,
+            (self.origin.0 + row, self.origin.1 + col),
+            (rows.min(self.rows - row), cols.min(self.cols - col)),
+        )
+    }
+
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_rows((0..self.rows).map(|row| self.row(row).to_vec())).unwrap()
+    }
+}
+
+impl<T> Index<(usize, usize)> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &Self::Output {
+        self.get(position).unwrap_or_else(|| {
+            panic!(
+                "position {:?} is outside of the {}x{} view",
+                position, self.rows, self.cols
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn construct_and_index() {
+        let mut grid = Grid::new(2, 3, 0);
+        grid[(1, 2)] = 5;
+        *grid.get_mut((0, 1)).unwrap() = 1;
+        assert_eq!(grid.row(0), &[0, 1, 0]);
+        assert_eq!(grid.row(1), &[0, 0, 5]);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, 3)), None);
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+
+        assert_eq!(
+            Grid::from_vec(2, 3, vec![0, 1, 0, 0, 0, 5]),
+            Some(grid.clone())
+        );
+        assert_eq!(Grid::from_vec(2, 2, vec![0; 3]), None);
+        assert_eq!(Grid::<u8>::from_vec(usize::MAX, 2, vec![]), None);
+
+        let no_cols = Grid::<u8>::new(3, 0, 0);
+        assert_eq!(no_cols.iter_rows().count(), 3);
+        assert_eq!(no_cols.to_rows(), vec![Vec::<u8>::new(); 3]);
+        assert_eq!(
+            Grid::from_rows(vec![vec![0, 1, 0], vec![0, 0, 5]]),
+            Ok(grid)
+        );
+        assert_eq!(
+            Grid::from_rows(vec![vec![0, 1], vec![0]]),
+            Err(GridErr::Ragged {
+                row: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+
+        let empty: Grid<u8> = Grid::from_rows(Vec::<Vec<u8>>::new()).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.iter_rows().count(), 0);
+        assert_eq!(empty.positions().count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let grid = Grid::new(2, 2, ());
+        let _ = grid[(0, 2)];
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::new(3, 4, ());
+        let mut four: Vec<_> = grid.neighbours((1, 1), Connectivity::Four).collect();
+        four.sort_unstable();
+        assert_eq!(four, vec![(0, 1), (1, 0), (1, 2), (2, 1)]);
+        assert_eq!(grid.neighbours((1, 1), Connectivity::Eight).count(), 8);
+
+        let mut corner: Vec<_> = grid.neighbours((0, 3), Connectivity::Eight).collect();
+        corner.sort_unstable();
+        assert_eq!(corner, vec![(0, 2), (1, 2), (1, 3)]);
+        assert_eq!(grid.neighbours((2, 0), Connectivity::Four).count(), 2);
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid: Grid<char> = "
+            #..#
+            .##.
+        "
+        .parse()
+        .unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (2, 4));
+        assert_eq!(grid.to_string(), "#..#\n.##.\n");
+
+        let blocked = Grid::parse("#.\n.#", |ch| match ch {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap();
+        let cells: Vec<_> = blocked.iter().filter(|(_, &b)| b).map(|(p, _)| p).collect();
+        assert_eq!(cells, vec![(0, 0), (1, 1)]);
+        assert_eq!(
+            Grid::parse("#.\n.x", |ch| if ch == 'x' { None } else { Some(ch) }),
+            Err(GridErr::InvalidCell {
+                row: 1,
+                col: 1,
+                ch: 'x'
+            })
+        );
+        assert!(matches!(
+            "ab\nc".parse::<Grid<char>>(),
+            Err(GridErr::Ragged { row: 1, .. })
+        ));
+    }
+
+    fn numbers(rows: Vec<Vec<i32>>) -> Grid<i32> {
+        Grid::from_rows(rows).unwrap()
+    }
+
+    #[test]
+    fn rectangular_rotations() {
+        let original = numbers(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        assert!(!original.is_square());
+
+        let mut grid = original.clone();
+        grid.rotate_90();
+        assert_eq!(grid.to_rows(), vec![vec![4, 1], vec![5, 2], vec![6, 3]]);
+        grid.rotate_90();
+        assert_eq!(grid.to_rows(), vec![vec![6, 5, 4], vec![3, 2, 1]]);
+
+        let mut half = original.clone();
+        half.rotate_180();
+        assert_eq!(half, grid);
+
+        let mut grid = original.clone();
+        grid.rotate_270();
+        assert_eq!(grid.to_rows(), vec![vec![3, 6], vec![2, 5], vec![1, 4]]);
+        grid.rotate_90();
+        assert_eq!(grid, original);
+
+        let mut grid = original.clone();
+        grid.transpose();
+        assert_eq!(grid.to_rows(), vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+        grid.transpose();
+        assert_eq!(grid, original);
+    }
+
+    #[test]
+    fn square_transforms() {
+        let original = numbers(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]);
+        let mut grid = original.clone();
+        grid.flip_horizontal();
+        assert_eq!(
+            grid.to_rows(),
+            vec![vec![3, 2, 1], vec![6, 5, 4], vec![9, 8, 7]]
+        );
+        let mut grid = original.clone();
+        grid.flip_vertical();
+        assert_eq!(
+            grid.to_rows(),
+            vec![vec![7, 8, 9], vec![4, 5, 6], vec![1, 2, 3]]
+        );
+
+        let mut grid = original.clone();
+        for _ in 0..4 {
+            grid.rotate_90();
+        }
+        assert_eq!(grid, original);
+        grid.rotate_90();
+        grid.rotate_270();
+        assert_eq!(grid, original);
+
+        let mut empty = Grid::new(0, 3, 0u8);
+        empty.rotate_90();
+        assert_eq!((empty.rows(), empty.cols()), (3, 0));
+        empty.flip_horizontal();
+        empty.zero_rows_and_cols();
+    }
+
+    #[test]
+    fn zero_rows_and_cols() {
+        let mut grid = numbers(vec![
+            vec![1, 2, 0],
+            vec![4, 5, 6],
+            vec![7, 8, 9],
+            vec![0, 1, 2],
+        ]);
+        grid.zero_rows_and_cols();
+        assert_eq!(
+            grid.to_rows(),
+            vec![vec![0, 0, 0], vec![0, 5, 0], vec![0, 8, 0], vec![0, 0, 0]]
+        );
+
+        let mut grid = Grid::from_rows(vec![vec!["a", ""], vec!["b", "c"]]).unwrap();
+        grid.zero_rows_and_cols();
+        assert_eq!(grid.to_rows(), vec![vec!["", ""], vec!["b", ""]]);
+    }
+
+    #[test]
+    fn views() {
+        let grid = Grid::from_vec(4, 4, (0..16).collect()).unwrap();
+        let view = grid.view((1, 1), 2, 3);
+        assert_eq!((view.rows(), view.cols()), (2, 3));
+        assert_eq!(view[(0, 0)], 5);
+        assert_eq!(view.row(1), &[9, 10, 11]);
+        assert_eq!(view.get((2, 0)), None);
+
+        let inner = view.view((1, 1), 5, 5);
+        assert_eq!((inner.rows(), inner.cols()), (1, 2));
+        assert_eq!(
+            inner.to_grid(),
+            Grid::from_rows(vec![vec![10, 11]]).unwrap()
+        );
+
+        let clipped = grid.view((3, 3), 2, 2);
+        assert_eq!(clipped.to_grid(), Grid::new(1, 1, 15));
+        assert_eq!(grid.view((5, 5), 2, 2).rows(), 0);
+        assert_eq!(grid.map(|n| n % 2).row(0), &[0, 1, 0, 1]);
+    }
+}
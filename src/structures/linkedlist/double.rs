@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell, RefMut};
 use std::fmt;
 use std::fmt::Display;
 use std::iter::FromIterator;
@@ -28,12 +28,16 @@ impl<T: fmt::Debug> fmt::Debug for Node<T> {
 /// # Changes
 /// - Removed some of the global bounds on T and added them where needed,
 /// - Added [`values`] method which retruns an iterator on T (helps with testing)
+/// - Added [`value_refs`] which iterates on the inner values without requiring `T: Clone`
+/// - Added an owning [`IntoIterator`] and [`Extend`] implementation
+/// - Added [`pop_front`], [`pop_back`] and [`reverse`]
 ///
 /// [`values`]: #method.values
-impl<T> LinkedList<T>
-where
-    T: std::clone::Clone,
-{
+/// [`value_refs`]: #method.value_refs
+/// [`pop_front`]: #method.pop_front
+/// [`pop_back`]: #method.pop_back
+/// [`reverse`]: #method.reverse
+impl<T> LinkedList<T> {
     pub fn new() -> Self {
         Self {
             head: None,
@@ -103,6 +107,61 @@ where
         }
     }
 
+    /// Returns an iterator of [ValueRef], which gives access to the inner values of the list
+    /// without cloning them.
+    pub fn value_refs(&self) -> ValueRefs<T> {
+        ValueRefs { base: self.iter() }
+    }
+
+    /// Removes the first element of the LinkedList
+    pub fn pop_front(&mut self) -> Option<NodeRef<T>> {
+        let head = self.head.take()?;
+        match head.borrow_mut().next.take() {
+            Some(next) => {
+                next.borrow_mut().prev = None;
+                self.head = Some(next);
+            }
+            None => self.tail = None,
+        }
+        Some(head)
+    }
+
+    /// Removes the last element of the LinkedList
+    pub fn pop_back(&mut self) -> Option<NodeRef<T>> {
+        let tail = self.tail.take()?;
+        match tail.borrow_mut().prev.take() {
+            Some(prev) => {
+                prev.borrow_mut().next = None;
+                self.tail = Some(prev);
+            }
+            None => self.head = None,
+        }
+        Some(tail)
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Reverses the list in place by swapping the `next` and `prev` links of every node.
+    pub fn reverse(&mut self) {
+        let mut curr = self.head.clone();
+        while let Some(node) = curr {
+            let mut node = node.borrow_mut();
+            let node = &mut *node;
+            std::mem::swap(&mut node.next, &mut node.prev);
+            // `prev` is the old `next` after the swap
+            curr = node.prev.clone();
+        }
+        std::mem::swap(&mut self.head, &mut self.tail);
+    }
+}
+
+impl<T: Clone> LinkedList<T> {
     pub fn values(&self) -> ValuesIter<T> {
         ValuesIter { base: self.iter() }
     }
@@ -117,19 +176,75 @@ impl<T> Drop for LinkedList<T> {
     }
 }
 
-impl<T> FromIterator<T> for LinkedList<T>
-where
-    T: Clone,
-{
+impl<T> FromIterator<T> for LinkedList<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut c = LinkedList::new();
+        c.extend(iter);
+        c
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for i in iter {
-            c.append(i);
+            self.append(i);
         }
-        c
     }
 }
 
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let len = self.len();
+        IntoIter { list: self, len }
+    }
+}
+
+/// Owning iterator over the values of a [LinkedList].
+///
+/// # Panics
+/// Since the values are moved out of the nodes, `next` and `next_back` will panic if a node is
+/// still referenced from somewhere else (ie. a node that was kept around from [`LinkedList::iter`])
+#[derive(Debug)]
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+    len: usize,
+}
+
+impl<T> IntoIter<T> {
+    fn unwrap_node(&mut self, node: NodeRef<T>) -> T {
+        self.len -= 1;
+        match Rc::try_unwrap(node) {
+            Ok(node) => node.into_inner().data,
+            Err(_) => panic!("cannot move value out of a node that is still shared"),
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.list.pop_front()?;
+        Some(self.unwrap_node(node))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.list.pop_back()?;
+        Some(self.unwrap_node(node))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
 #[derive(Debug)]
 pub struct Iter<T> {
     next: Option<NodeRef<T>>,
@@ -201,6 +316,38 @@ impl<'a, T: Clone> DoubleEndedIterator for ValuesIter<T> {
     }
 }
 
+/// Borrowed view on the value of a node, returned by [`LinkedList::value_refs`].
+#[derive(Debug)]
+pub struct ValueRef<T>(NodeRef<T>);
+
+impl<T> ValueRef<T> {
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.0.borrow(), |node| &node.data)
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.0.borrow_mut(), |node| &mut node.data)
+    }
+}
+
+#[derive(Debug)]
+pub struct ValueRefs<T> {
+    base: Iter<T>,
+}
+
+impl<T> Iterator for ValueRefs<T> {
+    type Item = ValueRef<T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.base.next().map(ValueRef)
+    }
+}
+
+impl<T> DoubleEndedIterator for ValueRefs<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.base.next_back().map(ValueRef)
+    }
+}
+
 impl<T: Display> Display for LinkedList<T> {
     fn fmt(&self, w: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
         write!(w, "[")?;
@@ -359,4 +506,49 @@ mod tests {
         assert_eq!(iter.next(), None);
         assert_eq!(iter.next_back(), None);
     }
+
+    #[test]
+    fn into_iter_double_ended() {
+        let list: LinkedList<_> = vec!["a", "b", "c", "d"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some("a".to_string()));
+        assert_eq!(iter.next_back(), Some("d".to_string()));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next_back(), Some("c".to_string()));
+        assert_eq!(iter.next(), Some("b".to_string()));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn extend() {
+        let mut list = LinkedList::from_iter(vec![1, 2]);
+        list.extend(vec![3, 4]);
+        assert_eq!(list.values().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(list.values().rev().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn value_refs() {
+        let list = LinkedList::from_iter(vec![1, 2, 3]);
+        for value in list.value_refs().rev() {
+            *value.borrow_mut() += 1;
+        }
+        let values: Vec<_> = list.value_refs().map(|v| *v.borrow()).collect();
+        assert_eq!(values, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list = LinkedList::from_iter(vec![1, 2, 3, 4]);
+        list.reverse();
+        assert_eq!(list.values().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert_eq!(list.values().rev().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        list.append(0);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![4, 3, 2, 1, 0]);
+    }
 }
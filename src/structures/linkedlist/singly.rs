@@ -1,4 +1,5 @@
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell, RefMut};
+use std::cmp::Ordering;
 use std::convert::From;
 use std::fmt;
 use std::fmt::Display;
@@ -20,8 +21,18 @@ pub struct HashedNode<T>(NodeRef<T>);
 /// - Added [`pop_front`] method which removes the first element of the linkedlist
 /// - Added [`remove_next`] method on [Node], which makes is a little easier to write
 /// functions operating directly on nodes and is quicker than traversing the while list.
+/// - Added [`value_refs`] which iterates on the inner values without requiring `T: Clone`
+/// - Added an owning [`IntoIterator`] and [`Extend`] implementation
+/// - Added [`reverse`] which reverses the list in place by relinking the nodes.
+/// - Added [`sort_by`] (and friends), [`merge_sorted`] and [`dedup_all_by`], which all relink
+///   the existing nodes rather than cloning the data.
 ///
+/// [`sort_by`]: #method.sort_by
+/// [`merge_sorted`]: #method.merge_sorted
+/// [`dedup_all_by`]: #method.dedup_all_by
 /// [`values`]: #method.values
+/// [`value_refs`]: #method.value_refs
+/// [`reverse`]: #method.reverse
 /// [`pop_front`]: #method.pop_front
 /// [`ll-url`]: https://github.com/modulitos/CtCI-rust
 /// [`remove_next`]: Node::remove_next
@@ -67,10 +78,7 @@ impl<T: fmt::Debug> fmt::Debug for Node<T> {
     }
 }
 
-impl<T> LinkedList<T>
-where
-    T: std::clone::Clone,
-{
+impl<T> LinkedList<T> {
     pub fn new() -> Self {
         Self { head: None }
     }
@@ -152,11 +160,171 @@ where
         }
     }
 
+    /// Returns an iterator of [ValueRef], which gives access to the inner values of the list
+    /// without cloning them.
+    pub fn value_refs(&self) -> ValueRefs<T> {
+        ValueRefs { base: self.iter() }
+    }
+
+    /// Number of nodes in the list.
+    ///
+    /// # Warning
+    /// Like [`tail`](#method.tail) this is not loop safe.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Reverses the list in place by relinking the nodes, no data is moved or cloned.
+    pub fn reverse(&mut self) {
+        let mut prev: Option<NodeRef<T>> = None;
+        let mut curr = self.head.take();
+        while let Some(node) = curr {
+            curr = std::mem::replace(&mut node.borrow_mut().next, prev);
+            prev = Some(node);
+        }
+        self.head = prev;
+    }
+}
+
+impl<T: Clone> LinkedList<T> {
     pub fn values(&self) -> ValuesIter<T> {
         ValuesIter { base: self.iter() }
     }
 }
 
+impl<T> LinkedList<T> {
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut f: F) {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Stable merge sort, the nodes are relinked in place so no data is moved or cloned.
+    ///
+    /// # Warning
+    /// Like [`tail`](#method.tail) this is not loop safe.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        let len = self.len();
+        let head = self.head.take();
+        self.head = sort_nodes(head, len, &mut compare);
+    }
+
+    /// Merges two sorted lists into a single sorted list, taking from `a` first when values are
+    /// equal.
+    pub fn merge_sorted(a: Self, b: Self) -> Self
+    where
+        T: Ord,
+    {
+        Self::merge_sorted_by(a, b, T::cmp)
+    }
+
+    pub fn merge_sorted_by<F: FnMut(&T, &T) -> Ordering>(
+        mut a: Self,
+        mut b: Self,
+        mut compare: F,
+    ) -> Self {
+        Self {
+            head: merge_nodes(a.head.take(), b.head.take(), &mut compare),
+        }
+    }
+
+    /// Removes the values equal to an earlier one, see [`dedup_all_by`][Self::dedup_all_by]
+    pub fn dedup_all(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_all_by(|a, b| a == b);
+    }
+
+    /// Removes every node for which `same` returns true when compared with an earlier node.
+    ///
+    /// The duplicates can be anywhere in the list. This is why it is not called `dedup_by`: the
+    /// name would suggest it behaves like [`Vec::dedup_by`], which only removes consecutive
+    /// duplicates. This uses the runner technique so it doesn't need any buffer (or hashing) at
+    /// the cost of being O(n²).
+    pub fn dedup_all_by<F: FnMut(&T, &T) -> bool>(&mut self, mut same: F) {
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            let mut runner = Rc::clone(&node);
+            loop {
+                let next = runner.borrow().next.clone();
+                let next = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+                if same(&node.borrow().data, &next.borrow().data) {
+                    runner.borrow_mut().next = next.borrow_mut().next.take();
+                } else {
+                    runner = next;
+                }
+            }
+            current = node.borrow().next.clone();
+        }
+    }
+}
+
+fn sort_nodes<T, F: FnMut(&T, &T) -> Ordering>(
+    head: Option<NodeRef<T>>,
+    len: usize,
+    compare: &mut F,
+) -> Option<NodeRef<T>> {
+    if len <= 1 {
+        return head;
+    }
+    let mid = len / 2;
+    let mut left_tail = Rc::clone(head.as_ref().unwrap());
+    for _ in 1..mid {
+        let next = Rc::clone(left_tail.borrow().next.as_ref().unwrap());
+        left_tail = next;
+    }
+    let right = left_tail.borrow_mut().next.take();
+
+    let left = sort_nodes(head, mid, compare);
+    let right = sort_nodes(right, len - mid, compare);
+    merge_nodes(left, right, compare)
+}
+
+fn merge_nodes<T, F: FnMut(&T, &T) -> Ordering>(
+    mut left: Option<NodeRef<T>>,
+    mut right: Option<NodeRef<T>>,
+    compare: &mut F,
+) -> Option<NodeRef<T>> {
+    let mut head: Option<NodeRef<T>> = None;
+    let mut tail: Option<NodeRef<T>> = None;
+    loop {
+        // Only take from the right side when it is strictly smaller to keep the sort stable
+        let side = match (&left, &right) {
+            (Some(l), Some(r)) => {
+                if compare(&r.borrow().data, &l.borrow().data) == Ordering::Less {
+                    &mut right
+                } else {
+                    &mut left
+                }
+            }
+            (Some(_), None) => &mut left,
+            (None, Some(_)) => &mut right,
+            (None, None) => break,
+        };
+        let node = side.take().unwrap();
+        *side = node.borrow_mut().next.take();
+        match tail {
+            Some(ref t) => t.borrow_mut().next = Some(Rc::clone(&node)),
+            None => head = Some(Rc::clone(&node)),
+        }
+        tail = Some(node);
+    }
+    head
+}
+
 impl<T> Drop for LinkedList<T> {
     fn drop(&mut self) {
         let mut cur_link = self.head.take();
@@ -166,19 +334,69 @@ impl<T> Drop for LinkedList<T> {
     }
 }
 
-impl<T> FromIterator<T> for LinkedList<T>
-where
-    T: Clone,
-{
+impl<T> FromIterator<T> for LinkedList<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut c = LinkedList::new();
-        for i in iter {
-            c.append(i);
-        }
+        c.extend(iter);
         c
     }
 }
 
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // Only walk to the tail once instead of once per appended value
+        let mut tail = self.tail();
+        for data in iter {
+            let node = Rc::new(RefCell::new(Node { data, next: None }));
+            match tail {
+                Some(ref t) => t.borrow_mut().next = Some(Rc::clone(&node)),
+                None => self.head = Some(Rc::clone(&node)),
+            }
+            tail = Some(node);
+        }
+    }
+}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let len = self.len();
+        IntoIter { list: self, len }
+    }
+}
+
+/// Owning iterator over the values of a [LinkedList].
+///
+/// # Panics
+/// Since the values are moved out of the nodes, `next` will panic if a node is still referenced
+/// from somewhere else (ie. a [NodeRef] that was kept around, or a node shared by two lists).
+#[derive(Debug)]
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+    len: usize,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.list.pop_front()?;
+        self.len -= 1;
+        match Rc::try_unwrap(node) {
+            Ok(node) => Some(node.into_inner().data),
+            Err(_) => panic!("cannot move value out of a node that is still shared"),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
 #[derive(Debug)]
 pub struct Iter<T> {
     next: Option<NodeRef<T>>,
@@ -217,6 +435,32 @@ impl<'a, T: Clone> Iterator for ValuesIter<T> {
     }
 }
 
+/// Borrowed view on the value of a node, returned by [`LinkedList::value_refs`].
+#[derive(Debug)]
+pub struct ValueRef<T>(NodeRef<T>);
+
+impl<T> ValueRef<T> {
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.0.borrow(), |node| &node.data)
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.0.borrow_mut(), |node| &mut node.data)
+    }
+}
+
+#[derive(Debug)]
+pub struct ValueRefs<T> {
+    base: Iter<T>,
+}
+
+impl<T> Iterator for ValueRefs<T> {
+    type Item = ValueRef<T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.base.next().map(ValueRef)
+    }
+}
+
 impl<T: Display> Display for LinkedList<T> {
     fn fmt(&self, w: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
         write!(w, "[")?;
@@ -447,4 +691,110 @@ mod tests {
         assert_eq!(set.contains(&HashedNode::from(4)), false);
         assert_eq!(set.len(), 4);
     }
+
+    #[test]
+    fn into_iter_owned() {
+        // `String` is used on purpose, the owning iterator should not need to clone
+        let list: LinkedList<_> = vec!["a", "b", "c"].into_iter().map(String::from).collect();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next(), Some("a".to_string()));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.collect::<Vec<_>>(), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn extend() {
+        let mut list = LinkedList::new();
+        list.extend(vec![1, 2]);
+        list.extend(vec![3, 4]);
+        list.extend(Vec::new());
+        assert_eq!(list.values().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn value_refs() {
+        let list = LinkedList::from_iter(vec![1, 2, 3]);
+        for value in list.value_refs() {
+            *value.borrow_mut() *= 10;
+        }
+        let values: Vec<_> = list.value_refs().map(|v| *v.borrow()).collect();
+        assert_eq!(values, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list = LinkedList::from_iter(vec![1, 2, 3, 4]);
+        list.reverse();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+
+        let mut list: LinkedList<i32> = LinkedList::new();
+        list.reverse();
+        assert!(list.is_empty());
+
+        let mut list = LinkedList::from_iter(vec![1]);
+        list.reverse();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn sort() {
+        let mut list = LinkedList::from_iter(vec![5, 1, 4, 2, 3, 2, 0]);
+        list.sort();
+        assert_eq!(list.values().collect::<Vec<_>>(), vec![0, 1, 2, 2, 3, 4, 5]);
+
+        let mut list: LinkedList<i32> = LinkedList::new();
+        list.sort();
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let items = vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e'), (3, 'f')];
+        let mut list = LinkedList::from_iter(items.clone());
+        list.sort_by_key(|&(key, _)| key);
+
+        let mut expected = items;
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(list.values().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn sort_relinks_nodes() {
+        let list = LinkedList::from_iter(vec![3, 1, 2]);
+        let nodes: Vec<_> = list.iter().collect();
+        let mut list = list;
+        list.sort();
+        let sorted: Vec<_> = list.iter().collect();
+        assert!(Rc::ptr_eq(&sorted[0], &nodes[1]));
+        assert!(Rc::ptr_eq(&sorted[1], &nodes[2]));
+        assert!(Rc::ptr_eq(&sorted[2], &nodes[0]));
+    }
+
+    #[test]
+    fn merge_sorted() {
+        let a = LinkedList::from_iter(vec![1, 3, 5, 7]);
+        let b = LinkedList::from_iter(vec![2, 3, 4]);
+        let merged = LinkedList::merge_sorted(a, b);
+        assert_eq!(
+            merged.values().collect::<Vec<_>>(),
+            vec![1, 2, 3, 3, 4, 5, 7]
+        );
+
+        let a = LinkedList::from_iter(vec![1, 2]);
+        let merged = LinkedList::merge_sorted(a, LinkedList::new());
+        assert_eq!(merged.values().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn dedup_all() {
+        let mut list = LinkedList::from_iter(vec![1, 2, 1, 3, 2, 2, 4, 1]);
+        list.dedup_all();
+        assert_eq!(list.values().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+        let mut list = LinkedList::from_iter(vec!["a", "B", "b", "A", "c"]);
+        list.dedup_all_by(|a, b| a.eq_ignore_ascii_case(b));
+        assert_eq!(list.values().collect::<Vec<_>>(), vec!["a", "B", "c"]);
+    }
 }
@@ -0,0 +1,178 @@
+use std::fmt;
+use std::io;
+
+/// Plain formats (`P1`, `P3`) store the pixels as ASCII numbers, binary ones (`P4`, `P6`) as
+/// raw bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Plain,
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum NetpbmErr {
+    Io(io::Error),
+    /// The file does not start with one of the expected magic numbers
+    InvalidMagic,
+    /// A header value or a plain pixel is not a valid number, or the dimensions are too large
+    InvalidNumber,
+    UnexpectedEof,
+    /// The image does not have the size of the destination
+    SizeMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// Only 8 bits samples are supported
+    UnsupportedMaxValue(usize),
+    /// A pixel has no matching color in the palette
+    UnknownColor([u8; 3]),
+}
+
+impl From<io::Error> for NetpbmErr {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl fmt::Display for NetpbmErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::InvalidMagic => write!(f, "invalid magic number"),
+            Self::InvalidNumber => write!(f, "invalid number"),
+            Self::UnexpectedEof => write!(f, "unexpected end of file"),
+            Self::SizeMismatch { expected, found } => write!(
+                f,
+                "expected a {}x{} image, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            Self::UnsupportedMaxValue(max) => write!(f, "unsupported max value {}", max),
+            Self::UnknownColor([r, g, b]) => write!(f, "unknown color ({}, {}, {})", r, g, b),
+        }
+    }
+}
+
+/// # Netpbm parser
+///
+/// Reads the header and raster of PBM and PPM images. Header values are separated by whitespace
+/// and may be interleaved with comments running from `#` to the end of the line.
+pub struct NetpbmParser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> NetpbmParser<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&byte) = self.data.get(self.pos) {
+            if byte == b'#' {
+                while self.data.get(self.pos).is_some_and(|&b| b != b'\n') {
+                    self.pos += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Reads the magic number and returns its digit, ie. `1` for `P1`
+    pub fn magic(&mut self) -> Result<u8, NetpbmErr> {
+        match self.data.get(self.pos..self.pos + 2) {
+            Some([b'P', digit]) if digit.is_ascii_digit() => {
+                self.pos += 2;
+                Ok(digit - b'0')
+            }
+            _ => Err(NetpbmErr::InvalidMagic),
+        }
+    }
+
+    pub fn number(&mut self) -> Result<usize, NetpbmErr> {
+        self.skip_whitespace_and_comments();
+        let start = self.pos;
+        while self.data.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return match self.data.get(self.pos) {
+                Some(_) => Err(NetpbmErr::InvalidNumber),
+                None => Err(NetpbmErr::UnexpectedEof),
+            };
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .unwrap()
+            .parse()
+            .map_err(|_| NetpbmErr::InvalidNumber)
+    }
+
+    /// Single pixel of a plain bitmap, which does not need to be separated from the next one
+    pub fn bit(&mut self) -> Result<bool, NetpbmErr> {
+        self.skip_whitespace_and_comments();
+        let bit = match self.data.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            Some(_) => return Err(NetpbmErr::InvalidNumber),
+            None => return Err(NetpbmErr::UnexpectedEof),
+        };
+        self.pos += 1;
+        Ok(bit)
+    }
+
+    /// Binary raster of `len` bytes, following the single whitespace which ends the header
+    pub fn raster(&mut self, len: usize) -> Result<&'a [u8], NetpbmErr> {
+        if !self.data.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            return Err(NetpbmErr::UnexpectedEof);
+        }
+        let start = self.pos + 1;
+        let raster = self
+            .data
+            .get(start..start + len)
+            .ok_or(NetpbmErr::UnexpectedEof)?;
+        self.pos = start + len;
+        Ok(raster)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_header() {
+        let data = b"P4 # comment\n# another one\n  12\t3\n\x01\x02\x03\x04\x05\x06";
+        let mut parser = NetpbmParser::new(data);
+        assert_eq!(parser.magic().unwrap(), 4);
+        assert_eq!(parser.number().unwrap(), 12);
+        assert_eq!(parser.number().unwrap(), 3);
+        assert_eq!(parser.raster(6).unwrap(), &[1, 2, 3, 4, 5, 6]);
+        assert!(matches!(parser.number(), Err(NetpbmErr::UnexpectedEof)));
+
+        let mut parser = NetpbmParser::new(b"P1 2 1 01");
+        parser.magic().unwrap();
+        parser.number().unwrap();
+        parser.number().unwrap();
+        assert_eq!(parser.bit().unwrap(), false);
+        assert_eq!(parser.bit().unwrap(), true);
+        assert!(matches!(parser.bit(), Err(NetpbmErr::UnexpectedEof)));
+    }
+
+    #[test]
+    fn invalid_headers() {
+        assert!(matches!(
+            NetpbmParser::new(b"GIF89a").magic(),
+            Err(NetpbmErr::InvalidMagic)
+        ));
+        let mut parser = NetpbmParser::new(b"P1 x");
+        parser.magic().unwrap();
+        assert!(matches!(parser.number(), Err(NetpbmErr::InvalidNumber)));
+        let mut parser = NetpbmParser::new(b"P4 1 1\n");
+        parser.magic().unwrap();
+        parser.number().unwrap();
+        parser.number().unwrap();
+        assert!(matches!(parser.raster(1), Err(NetpbmErr::UnexpectedEof)));
+    }
+}